@@ -0,0 +1,22 @@
+-- Results were created by create_results_tables before migrations existed, hence IF NOT EXISTS.
+CREATE TABLE IF NOT EXISTS race_results (
+    race_id INTEGER PRIMARY KEY REFERENCES races(id) ON DELETE CASCADE,
+    source TEXT NOT NULL,
+    recorded_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS horse_results (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    race_id INTEGER NOT NULL REFERENCES races(id) ON DELETE CASCADE,
+    horse_id INTEGER NOT NULL UNIQUE REFERENCES horses(id) ON DELETE CASCADE,
+    finish_position INTEGER,
+    margin REAL,
+    final_odds REAL,
+    win_payoff REAL,
+    place_payoff REAL,
+    show_payoff REAL,
+    claimed INTEGER NOT NULL DEFAULT 0,
+    claimed_by TEXT NOT NULL DEFAULT ''
+);
+
+CREATE INDEX IF NOT EXISTS idx_horse_results_race_id ON horse_results(race_id);
@@ -0,0 +1,3 @@
+-- Deletes, results lookups and the backtest all walk racecards -> races -> horses.
+CREATE INDEX IF NOT EXISTS idx_races_racecard_id ON races(racecard_id);
+CREATE INDEX IF NOT EXISTS idx_horses_race_id ON horses(race_id);
@@ -0,0 +1,32 @@
+-- One row per stored horse with the columns search results are filtered on.
+CREATE TABLE IF NOT EXISTS search_entries (
+    horse_id INTEGER PRIMARY KEY REFERENCES horses(id) ON DELETE CASCADE,
+    race_id INTEGER NOT NULL REFERENCES races(id) ON DELETE CASCADE,
+    racecard_id INTEGER NOT NULL REFERENCES racecards(id) ON DELETE CASCADE,
+    race_number INTEGER,
+    program_number TEXT NOT NULL,
+    track_code TEXT NOT NULL,
+    date TEXT NOT NULL,
+    surface TEXT NOT NULL,
+    race_type TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_search_entries_racecard_id ON search_entries(racecard_id);
+CREATE INDEX IF NOT EXISTS idx_search_entries_date ON search_entries(date);
+
+-- Names are matched through FTS5; the rowid is the horse id.
+CREATE VIRTUAL TABLE IF NOT EXISTS horse_search USING fts5(
+    horse_name,
+    trainer,
+    jockey,
+    sire,
+    dam,
+    owner,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+-- Virtual tables cannot carry foreign keys, so the index follows search_entries deletes.
+CREATE TRIGGER IF NOT EXISTS search_entries_after_delete AFTER DELETE ON search_entries
+BEGIN
+    DELETE FROM horse_search WHERE rowid = old.horse_id;
+END;
@@ -0,0 +1,16 @@
+-- Brisnet has no stable horse id, so a horse is identified by name, year of birth and dam.
+ALTER TABLE search_entries ADD COLUMN horse_key TEXT NOT NULL DEFAULT '';
+CREATE INDEX IF NOT EXISTS idx_search_entries_horse_key ON search_entries(horse_key);
+
+-- Existing rows have no key; clearing them makes startup re-index every card with keys filled in.
+DELETE FROM search_entries;
+
+CREATE TABLE IF NOT EXISTS watch_list (
+    horse_key TEXT PRIMARY KEY,
+    horse_name TEXT NOT NULL,
+    year_of_birth INTEGER,
+    dam TEXT NOT NULL,
+    note TEXT NOT NULL DEFAULT '',
+    added_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
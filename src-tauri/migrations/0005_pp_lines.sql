@@ -0,0 +1,24 @@
+-- One row per horse per past race. The same line is repeated on every card the horse appears on,
+-- so rows are keyed by the race and the horse rather than by the card.
+CREATE TABLE IF NOT EXISTS pp_lines (
+    track_code TEXT NOT NULL,
+    race_date TEXT NOT NULL,
+    race_number INTEGER NOT NULL,
+    horse_key TEXT NOT NULL,
+    surface TEXT NOT NULL,
+    distance_furlongs REAL,
+    track_condition TEXT NOT NULL,
+    entrants INTEGER,
+    post_position INTEGER,
+    first_call_position INTEGER,
+    finish_position INTEGER,
+    PRIMARY KEY (track_code, race_date, race_number, horse_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_pp_lines_track_surface ON pp_lines(track_code, surface, race_date);
+
+-- Cards whose past performances have been collected, so startup only scans new ones. Lines stay
+-- when a card is deleted; they describe races that were run, not the card.
+CREATE TABLE IF NOT EXISTS pp_line_sources (
+    racecard_id INTEGER PRIMARY KEY REFERENCES racecards(id) ON DELETE CASCADE
+);
@@ -0,0 +1,16 @@
+-- Connections and betting context for each start, so trainer and jockey statistics can be built from
+-- the same lines as the bias statistics.
+ALTER TABLE pp_lines ADD COLUMN trainer TEXT NOT NULL DEFAULT '' COLLATE NOCASE;
+ALTER TABLE pp_lines ADD COLUMN jockey TEXT NOT NULL DEFAULT '' COLLATE NOCASE;
+ALTER TABLE pp_lines ADD COLUMN race_type TEXT NOT NULL DEFAULT '';
+ALTER TABLE pp_lines ADD COLUMN days_since_last_race INTEGER;
+ALTER TABLE pp_lines ADD COLUMN first_lasix INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE pp_lines ADD COLUMN equipment_change TEXT NOT NULL DEFAULT '';
+ALTER TABLE pp_lines ADD COLUMN odds REAL;
+ALTER TABLE pp_lines ADD COLUMN win_payoff REAL;
+
+CREATE INDEX IF NOT EXISTS idx_pp_lines_trainer ON pp_lines(trainer);
+CREATE INDEX IF NOT EXISTS idx_pp_lines_jockey ON pp_lines(jockey);
+
+-- Existing lines have no connections; clearing the sources makes startup collect every card again.
+DELETE FROM pp_line_sources;
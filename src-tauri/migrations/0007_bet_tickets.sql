@@ -0,0 +1,17 @@
+-- Selections are kept as entered and as resolved (JSON arrays of program number lists).
+CREATE TABLE IF NOT EXISTS bet_tickets (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    racecard_id INTEGER NOT NULL REFERENCES racecards(id) ON DELETE CASCADE,
+    bet_type TEXT NOT NULL,
+    race_number INTEGER NOT NULL,
+    base_amount REAL NOT NULL,
+    structure TEXT NOT NULL,
+    selections TEXT NOT NULL,
+    legs TEXT NOT NULL,
+    combinations INTEGER NOT NULL,
+    total_cost REAL NOT NULL,
+    note TEXT NOT NULL DEFAULT '',
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_bet_tickets_racecard_id ON bet_tickets(racecard_id);
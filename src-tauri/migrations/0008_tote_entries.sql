@@ -0,0 +1,12 @@
+-- Odds and scratch changes as they were entered, newest last. A row carries odds, a scratch
+-- change or both.
+CREATE TABLE IF NOT EXISTS tote_entries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    horse_id INTEGER NOT NULL REFERENCES horses(id) ON DELETE CASCADE,
+    odds REAL,
+    scratched INTEGER,
+    source TEXT NOT NULL,
+    recorded_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_tote_entries_horse_id ON tote_entries(horse_id);
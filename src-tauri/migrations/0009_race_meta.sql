@@ -0,0 +1,6 @@
+-- The model output for a race as of its last scratch change, as RaceMeta JSON.
+CREATE TABLE IF NOT EXISTS race_meta (
+    race_id INTEGER PRIMARY KEY REFERENCES races(id) ON DELETE CASCADE,
+    meta TEXT NOT NULL,
+    computed_at TEXT NOT NULL
+);
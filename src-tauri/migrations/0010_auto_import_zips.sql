@@ -0,0 +1,8 @@
+-- Zips the folder watcher is done with, by path, size and modification time. Without it a card
+-- deleted in the app comes back from its download on the next launch. A zip that changes is
+-- looked at again.
+CREATE TABLE IF NOT EXISTS auto_import_zips (
+    path TEXT PRIMARY KEY,
+    size INTEGER NOT NULL,
+    modified INTEGER NOT NULL
+);
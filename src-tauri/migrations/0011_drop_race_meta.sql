@@ -0,0 +1,3 @@
+-- Stored race analysis was never read back and went stale when the model parameters changed;
+-- races are reranked on demand instead.
+DROP TABLE IF EXISTS race_meta;
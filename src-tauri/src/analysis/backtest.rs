@@ -0,0 +1,357 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use railbreaker_lib::contextual_speed_and_pace_model::RaceMeta;
+use railbreaker_lib::models::racecard::Race;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use crate::analysis::{distance_band, distance_furlongs, enum_label, surface_label};
+use crate::analysis::trip_model::{rank_race_trips, TripRanking};
+use crate::analysis::model_parameters::rank_race_with_parameters;
+use crate::commands::config_file_commands::{load_model_parameters, load_trip_weights};
+use crate::sqlite::db::make_read_only_pool;
+use crate::sqlite::racecards::read_racecard_by_id;
+use crate::sqlite::results::{normalize_program_number, read_results_by_racecard_id, HorseResult};
+use crate::states::global_state::global_state;
+
+const BASE_WAGER: f64 = 2.0;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BacktestBucket {
+    pub label: String,
+    pub races: usize,
+    pub top_pick_wins: usize,
+    pub top_pick_in_the_money: usize,
+    pub hit_rate: f64,
+    pub bets: usize,
+    pub bet_wins: usize,
+    pub wagered: f64,
+    pub returned: f64,
+    pub roi: f64,
+    // Races where the trip model had a pick (a runner with a positive trip score).
+    pub trip_races: usize,
+    pub trip_top_pick_wins: usize,
+    pub trip_hit_rate: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BacktestReport {
+    pub racecards: usize,
+    pub races_evaluated: usize,
+    pub races_skipped: usize,
+    pub overall: BacktestBucket,
+    pub by_confidence: Vec<BacktestBucket>,
+    pub by_shape: Vec<BacktestBucket>,
+    pub by_surface: Vec<BacktestBucket>,
+    pub by_distance: Vec<BacktestBucket>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RaceEvaluation {
+    pub confidence: String,
+    pub shape: String,
+    pub surface: String,
+    pub distance_band: String,
+    pub top_pick_won: bool,
+    pub top_pick_in_the_money: bool,
+    pub bet: Option<WinBetOutcome>,
+    pub trip_top_pick_won: Option<bool>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WinBetOutcome {
+    pub won: bool,
+    pub returned: f64,
+}
+
+pub async fn backtest_database(pool: &SqlitePool) -> Result<BacktestReport, String> {
+    let racecard_ids = sqlx::query_scalar::<_, i64>("SELECT id FROM racecards ORDER BY date ASC, id ASC;")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to load racecards: {}", e))?;
+
+    let trip_weights = load_trip_weights().await;
+    let parameters = load_model_parameters().await;
+    let mut accumulator = BacktestAccumulator::default();
+
+    for racecard_id in racecard_ids {
+        let results = read_results_by_racecard_id(pool, racecard_id)
+            .await
+            .map_err(|e| format!("Failed to load results for racecard {}: {}", racecard_id, e))?;
+        if results.is_empty() {
+            continue;
+        }
+
+        let racecard = read_racecard_by_id(pool, racecard_id)
+            .await
+            .map_err(|e| format!("Failed to load racecard {}: {}", racecard_id, e))?;
+        accumulator.racecards += 1;
+
+        for race in &racecard.races {
+            let race_results: Vec<&HorseResult> = results.iter().filter(|r| r.race_id == race.id).collect();
+            if race_results.is_empty() {
+                continue;
+            }
+
+            let meta = rank_race_with_parameters(race, Some(racecard.date.as_str()), &parameters);
+            let trips = rank_race_trips(race, Some(racecard.date.as_str()), &trip_weights);
+            match evaluate_race(race, &meta, &trips, &race_results) {
+                Some(evaluation) => accumulator.add(&evaluation),
+                None => accumulator.races_skipped += 1,
+            }
+        }
+    }
+
+    Ok(accumulator.into_report())
+}
+
+pub fn evaluate_race(
+    race: &Race,
+    meta: &RaceMeta,
+    trips: &TripRanking,
+    results: &[&HorseResult],
+) -> Option<RaceEvaluation> {
+    let rank_result = meta.race_rank_result.as_ref()?;
+    let top_pick = rank_result
+        .horses
+        .iter()
+        .filter(|h| h.score.is_some())
+        .max_by(|a, b| a.score.partial_cmp(&b.score).unwrap_or(std::cmp::Ordering::Equal))?;
+
+    let result_for = |program_number: &str| {
+        let program_number = normalize_program_number(program_number);
+        results
+            .iter()
+            .find(|r| normalize_program_number(&r.program_number) == program_number)
+    };
+
+    let winner_known = results.iter().any(|r| r.finish_position == Some(1));
+    if !winner_known {
+        return None;
+    }
+
+    let top_finish = result_for(&top_pick.program_number).and_then(|r| r.finish_position);
+
+    let bet = meta.win_bet.as_ref().and_then(|win_bet| {
+        let result = result_for(&win_bet.program_number)?;
+        if let (Some(min_odds), Some(final_odds)) = (win_bet.min_odds, result.final_odds) {
+            if final_odds < min_odds {
+                return None;
+            }
+        }
+
+        let won = result.finish_position == Some(1);
+        let returned = if won {
+            result
+                .win_payoff
+                .or(result.final_odds.map(|odds| (odds + 1.0) * BASE_WAGER))
+                .unwrap_or(0.0)
+        } else {
+            0.0
+        };
+
+        Some(WinBetOutcome { won, returned })
+    });
+
+    // Rankings are sorted by score, so the first runner is the trip pick when its score is positive.
+    let trip_top_pick_won = trips
+        .horses
+        .iter()
+        .find(|h| !h.scratched)
+        .filter(|h| h.score > 0.0)
+        .map(|h| result_for(&h.program_number).and_then(|r| r.finish_position) == Some(1));
+
+    let furlongs = distance_furlongs(race.distance.map(|d| d as f64)).unwrap_or(rank_result.distance_f);
+
+    Some(RaceEvaluation {
+        confidence: enum_label(&meta.confidence),
+        shape: enum_label(&meta.shape),
+        surface: surface_label(&race.surface).to_string(),
+        distance_band: distance_band(furlongs).to_string(),
+        top_pick_won: top_finish == Some(1),
+        top_pick_in_the_money: matches!(top_finish, Some(1..=3)),
+        bet,
+        trip_top_pick_won,
+    })
+}
+
+#[derive(Debug, Default)]
+pub struct BacktestAccumulator {
+    pub racecards: usize,
+    pub races_skipped: usize,
+    overall: BacktestBucket,
+    by_confidence: BTreeMap<String, BacktestBucket>,
+    by_shape: BTreeMap<String, BacktestBucket>,
+    by_surface: BTreeMap<String, BacktestBucket>,
+    by_distance: BTreeMap<String, BacktestBucket>,
+}
+
+impl BacktestAccumulator {
+    pub fn add(&mut self, evaluation: &RaceEvaluation) {
+        add_to_bucket(&mut self.overall, evaluation);
+        add_to_bucket(self.by_confidence.entry(evaluation.confidence.clone()).or_default(), evaluation);
+        add_to_bucket(self.by_shape.entry(evaluation.shape.clone()).or_default(), evaluation);
+        add_to_bucket(self.by_surface.entry(evaluation.surface.clone()).or_default(), evaluation);
+        add_to_bucket(self.by_distance.entry(evaluation.distance_band.clone()).or_default(), evaluation);
+    }
+
+    pub fn into_report(self) -> BacktestReport {
+        let finish = |map: BTreeMap<String, BacktestBucket>| -> Vec<BacktestBucket> {
+            map.into_iter()
+                .map(|(label, bucket)| finish_bucket(BacktestBucket { label, ..bucket }))
+                .collect()
+        };
+
+        BacktestReport {
+            racecards: self.racecards,
+            races_evaluated: self.overall.races,
+            races_skipped: self.races_skipped,
+            overall: finish_bucket(BacktestBucket { label: "All races".to_string(), ..self.overall }),
+            by_confidence: finish(self.by_confidence),
+            by_shape: finish(self.by_shape),
+            by_surface: finish(self.by_surface),
+            by_distance: finish(self.by_distance),
+        }
+    }
+}
+
+fn add_to_bucket(bucket: &mut BacktestBucket, evaluation: &RaceEvaluation) {
+    bucket.races += 1;
+    if evaluation.top_pick_won {
+        bucket.top_pick_wins += 1;
+    }
+    if evaluation.top_pick_in_the_money {
+        bucket.top_pick_in_the_money += 1;
+    }
+    if let Some(bet) = &evaluation.bet {
+        bucket.bets += 1;
+        bucket.wagered += BASE_WAGER;
+        bucket.returned += bet.returned;
+        if bet.won {
+            bucket.bet_wins += 1;
+        }
+    }
+    if let Some(won) = evaluation.trip_top_pick_won {
+        bucket.trip_races += 1;
+        if won {
+            bucket.trip_top_pick_wins += 1;
+        }
+    }
+}
+
+fn finish_bucket(mut bucket: BacktestBucket) -> BacktestBucket {
+    if bucket.races > 0 {
+        bucket.hit_rate = bucket.top_pick_wins as f64 / bucket.races as f64;
+    }
+    if bucket.wagered > 0.0 {
+        bucket.roi = (bucket.returned - bucket.wagered) / bucket.wagered;
+    }
+    if bucket.trip_races > 0 {
+        bucket.trip_hit_rate = bucket.trip_top_pick_wins as f64 / bucket.trip_races as f64;
+    }
+    bucket
+}
+
+pub fn format_report(report: &BacktestReport) -> String {
+    let mut out = String::new();
+    out.push_str(&format!(
+        "Racecards: {}  Races evaluated: {}  Races skipped: {}\n",
+        report.racecards, report.races_evaluated, report.races_skipped
+    ));
+
+    let sections = [
+        ("Overall", std::slice::from_ref(&report.overall)),
+        ("By confidence", report.by_confidence.as_slice()),
+        ("By shape", report.by_shape.as_slice()),
+        ("By surface", report.by_surface.as_slice()),
+        ("By distance", report.by_distance.as_slice()),
+    ];
+
+    for (title, buckets) in sections {
+        out.push_str(&format!("\n{}\n", title));
+        out.push_str(&format!(
+            "  {:<22} {:>6} {:>6} {:>7} {:>6} {:>6} {:>9} {:>9} {:>8} {:>6} {:>7}\n",
+            "", "Races", "Wins", "Hit %", "ITM", "Bets", "Wagered", "Returned", "ROI %", "Trips", "Trip %"
+        ));
+        for bucket in buckets {
+            out.push_str(&format!(
+                "  {:<22} {:>6} {:>6} {:>7.1} {:>6} {:>6} {:>9.2} {:>9.2} {:>8.1} {:>6} {:>7.1}\n",
+                bucket.label,
+                bucket.races,
+                bucket.top_pick_wins,
+                bucket.hit_rate * 100.0,
+                bucket.top_pick_in_the_money,
+                bucket.bets,
+                bucket.wagered,
+                bucket.returned,
+                bucket.roi * 100.0,
+                bucket.trip_races,
+                bucket.trip_hit_rate * 100.0
+            ));
+        }
+    }
+
+    out
+}
+
+pub async fn run_headless(args: Vec<String>) -> Result<(), String> {
+    let mut working_directory = None;
+    let mut database_path = None;
+    let mut as_json = false;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dir" => working_directory = Some(PathBuf::from(args.next().ok_or("--dir requires a path")?)),
+            "--db" => database_path = Some(args.next().ok_or("--db requires a path")?),
+            "--json" => as_json = true,
+            "-h" | "--help" => {
+                println!("Usage: railbreaker-backtest [--dir <working directory>] [--db <railbreaker.db>] [--json]");
+                return Ok(());
+            }
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+    }
+
+    // The working directory holds config.json, so the model parameters and trip weights are the
+    // ones the app uses.
+    let current_directory = match working_directory {
+        Some(dir) => dir,
+        None => std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?,
+    };
+    {
+        let mut gs = global_state()
+            .lock()
+            .map_err(|e| format!("Failed to lock global state: {}", e))?;
+        gs.current_directory = current_directory.to_string_lossy().to_string();
+        gs.racecards_directory = current_directory.join("Racecards").to_string_lossy().to_string();
+    }
+
+    let database_path = match database_path {
+        Some(path) => path,
+        None => current_directory
+            .join("Racecards")
+            .join("railbreaker.db")
+            .to_string_lossy()
+            .to_string(),
+    };
+
+    // A backtest only reads: a missing database is an error rather than a new empty one.
+    if !Path::new(&database_path).is_file() {
+        return Err(format!("Database '{}' does not exist", database_path));
+    }
+    let pool = make_read_only_pool(&database_path)
+        .await
+        .map_err(|e| format!("Failed to open database '{}': {}", database_path, e))?;
+
+    let report = backtest_database(&pool).await?;
+
+    if as_json {
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| format!("Failed to serialize backtest report: {}", e))?;
+        println!("{}", json);
+    } else {
+        print!("{}", format_report(&report));
+    }
+
+    Ok(())
+}
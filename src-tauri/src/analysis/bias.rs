@@ -0,0 +1,255 @@
+use std::collections::{BTreeMap, HashSet};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use crate::analysis::{distance_band, distance_furlongs, non_empty};
+use crate::sqlite::pp_lines::{read_pp_lines, PpLine, PpLineFilter};
+use crate::sqlite::racecards::read_racecard_by_id;
+
+// Buckets below these samples are still reported, just never flagged.
+const MIN_RACES_FOR_FLAGS: usize = 20;
+const MIN_BUCKET_STARTS: usize = 8;
+const STRONG_IMPACT: f64 = 1.3;
+const WEAK_IMPACT: f64 = 0.7;
+// How far back from the card date the racecard view looks.
+const RACECARD_BIAS_DAYS: i64 = 365;
+const MAX_POST_BUCKET: i64 = 12;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct BiasQuery {
+    pub track_code: String,
+    pub surface: Option<String>,
+    // One of the labels from analysis::distance_band.
+    pub distance_band: Option<String>,
+    pub condition: Option<String>,
+    // YYYYMMDD, inclusive.
+    pub date_from: Option<String>,
+    pub date_to: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BiasBucket {
+    pub label: String,
+    pub starts: usize,
+    pub wins: usize,
+    pub win_pct: f64,
+    // Wins divided by the wins field size alone would predict; 1.0 means no bias.
+    pub impact: f64,
+    #[serde(skip)]
+    expected_wins: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BiasReport {
+    pub track_code: String,
+    pub surface: Option<String>,
+    pub distance_band: Option<String>,
+    pub condition: Option<String>,
+    pub races: usize,
+    pub starts: usize,
+    pub by_post: Vec<BiasBucket>,
+    pub by_post_group: Vec<BiasBucket>,
+    pub by_first_call: Vec<BiasBucket>,
+    pub flags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RaceBias {
+    pub race_id: i64,
+    pub race_number: Option<i64>,
+    pub bias: BiasReport,
+}
+
+pub async fn track_bias(pool: &SqlitePool, query: &BiasQuery) -> Result<BiasReport, String> {
+    if query.track_code.trim().is_empty() {
+        return Err("A track code is required".to_string());
+    }
+
+    let filter = PpLineFilter {
+        track_code: Some(&query.track_code),
+        condition: non_empty(&query.condition),
+        date_from: non_empty(&query.date_from),
+        date_to: non_empty(&query.date_to),
+        ..PpLineFilter::default()
+    };
+    let mut lines = read_pp_lines(pool, &filter)
+        .await
+        .map_err(|e| format!("Failed to load past performances: {}", e))?;
+
+    // Surface codes are matched here rather than in SQL so "d" and "D" are the same surface.
+    if let Some(surface) = non_empty(&query.surface) {
+        lines.retain(|line| line.surface.trim().eq_ignore_ascii_case(surface.trim()));
+    }
+
+    Ok(compute_bias(&lines, query))
+}
+
+// Bias for each race on a card, from races run at the card's track on the same surface and
+// distance band during the year before the card.
+pub async fn racecard_bias(pool: &SqlitePool, racecard_id: i64) -> Result<Vec<RaceBias>, String> {
+    let racecard = read_racecard_by_id(pool, racecard_id)
+        .await
+        .map_err(|e| format!("Failed to load racecard: {}", e))?;
+
+    let date_from = chrono::NaiveDate::parse_from_str(&racecard.date, "%Y%m%d")
+        .ok()
+        .map(|date| (date - chrono::Duration::days(RACECARD_BIAS_DAYS)).format("%Y%m%d").to_string());
+
+    let mut biases = Vec::with_capacity(racecard.races.len());
+    for race in &racecard.races {
+        let query = BiasQuery {
+            track_code: racecard.track_code.clone(),
+            surface: Some(race.surface.clone()).filter(|s| !s.trim().is_empty()),
+            distance_band: distance_furlongs(race.distance.map(|d| d as f64)).map(|f| distance_band(f).to_string()),
+            condition: None,
+            date_from: date_from.clone(),
+            date_to: Some(racecard.date.clone()),
+        };
+
+        biases.push(RaceBias {
+            race_id: race.id,
+            race_number: race.race_number.map(|n| n as i64),
+            bias: track_bias(pool, &query).await?,
+        });
+    }
+
+    Ok(biases)
+}
+
+pub fn compute_bias(lines: &[PpLine], query: &BiasQuery) -> BiasReport {
+    let mut races = HashSet::new();
+    let mut overall = BiasBucket::default();
+    let mut by_post: BTreeMap<i64, BiasBucket> = BTreeMap::new();
+    let mut by_post_group = vec![
+        bucket("Inside (1-3)"),
+        bucket("Middle (4-7)"),
+        bucket("Outside (8+)"),
+    ];
+    let mut by_first_call = vec![
+        bucket("Leader"),
+        bucket("2nd-3rd"),
+        bucket("4th-6th"),
+        bucket("7th+"),
+    ];
+
+    for line in lines {
+        if let Some(band) = non_empty(&query.distance_band) {
+            let line_band = line.distance_furlongs.map(distance_band);
+            if line_band != Some(band) {
+                continue;
+            }
+        }
+
+        // Without a finish and a field size a line says nothing about bias.
+        let (finish, entrants) = match (line.finish_position, line.entrants) {
+            (Some(finish), Some(entrants)) if entrants > 1 => (finish, entrants),
+            _ => continue,
+        };
+        let won = finish == 1;
+        let expected = 1.0 / entrants as f64;
+
+        races.insert((line.race_date.clone(), line.race_number));
+        add(&mut overall, won, expected);
+
+        if let Some(post) = line.post_position.filter(|p| *p > 0) {
+            let key = post.min(MAX_POST_BUCKET);
+            let entry = by_post.entry(key).or_insert_with(|| {
+                bucket(&if key == MAX_POST_BUCKET { format!("{}+", key) } else { key.to_string() })
+            });
+            add(entry, won, expected);
+
+            let group = match post {
+                1..=3 => 0,
+                4..=7 => 1,
+                _ => 2,
+            };
+            add(&mut by_post_group[group], won, expected);
+        }
+
+        if let Some(position) = line.first_call_position {
+            let group = match position {
+                1 => 0,
+                2..=3 => 1,
+                4..=6 => 2,
+                _ => 3,
+            };
+            add(&mut by_first_call[group], won, expected);
+        }
+    }
+
+    let by_post_group: Vec<BiasBucket> = by_post_group.into_iter().map(finish_bucket).collect();
+    let by_first_call: Vec<BiasBucket> = by_first_call.into_iter().map(finish_bucket).collect();
+    let flags = bias_flags(races.len(), &by_post_group, &by_first_call);
+
+    BiasReport {
+        track_code: query.track_code.trim().to_uppercase(),
+        surface: non_empty(&query.surface).map(str::to_string),
+        distance_band: non_empty(&query.distance_band).map(str::to_string),
+        condition: non_empty(&query.condition).map(str::to_string),
+        races: races.len(),
+        starts: overall.starts,
+        by_post: by_post.into_values().map(finish_bucket).collect(),
+        by_post_group,
+        by_first_call,
+        flags,
+    }
+}
+
+fn bias_flags(races: usize, post_groups: &[BiasBucket], first_call: &[BiasBucket]) -> Vec<String> {
+    if races < MIN_RACES_FOR_FLAGS {
+        return Vec::new();
+    }
+
+    let strong = |b: &BiasBucket| b.starts >= MIN_BUCKET_STARTS && b.impact >= STRONG_IMPACT;
+    let weak = |b: &BiasBucket| b.starts >= MIN_BUCKET_STARTS && b.impact <= WEAK_IMPACT;
+    let (inside, outside) = (&post_groups[0], &post_groups[2]);
+    let leader = &first_call[0];
+
+    let mut flags = Vec::new();
+    if strong(inside) && strong(leader) {
+        flags.push(format!(
+            "Inside speed is winning: posts 1-3 win {:.0}% (impact {:.2}), first-call leaders win {:.0}% (impact {:.2})",
+            inside.win_pct * 100.0,
+            inside.impact,
+            leader.win_pct * 100.0,
+            leader.impact
+        ));
+    } else {
+        if strong(inside) {
+            flags.push(format!("Inside posts are winning: posts 1-3 win {:.0}% (impact {:.2})", inside.win_pct * 100.0, inside.impact));
+        }
+        if strong(leader) {
+            flags.push(format!("Early speed is winning: first-call leaders win {:.0}% (impact {:.2})", leader.win_pct * 100.0, leader.impact));
+        }
+    }
+    if strong(outside) {
+        flags.push(format!("Outside posts are winning: posts 8+ win {:.0}% (impact {:.2})", outside.win_pct * 100.0, outside.impact));
+    }
+    if weak(leader) {
+        flags.push(format!("Early speed is stopping: first-call leaders win {:.0}% (impact {:.2})", leader.win_pct * 100.0, leader.impact));
+    }
+
+    flags
+}
+
+fn bucket(label: &str) -> BiasBucket {
+    BiasBucket { label: label.to_string(), ..BiasBucket::default() }
+}
+
+fn add(bucket: &mut BiasBucket, won: bool, expected: f64) {
+    bucket.starts += 1;
+    bucket.expected_wins += expected;
+    if won {
+        bucket.wins += 1;
+    }
+}
+
+fn finish_bucket(mut bucket: BiasBucket) -> BiasBucket {
+    if bucket.starts > 0 {
+        bucket.win_pct = bucket.wins as f64 / bucket.starts as f64;
+    }
+    if bucket.expected_wins > 0.0 {
+        bucket.impact = bucket.wins as f64 / bucket.expected_wins;
+    }
+    bucket
+}
@@ -0,0 +1,239 @@
+use railbreaker_lib::models::racecard::{Horse, PastPerformance, Race};
+use serde::{Deserialize, Serialize};
+use crate::analysis::round_tenth;
+
+// Levels this far apart count as a drop or a rise; 5 points is about a 40% purse difference.
+pub const CLASS_CHANGE_POINTS: f64 = 5.0;
+// Tags within this fraction of each other are the same claiming level.
+const TAG_TOLERANCE: f64 = 0.1;
+const RECENT_LINES: usize = 3;
+
+// Brisnet race type codes. The class points move a race within its purse level, and the group is
+// what the connection statistics bucket by, so both reports read a race type the same way.
+pub struct RaceType {
+    pub code: &'static str,
+    pub group: &'static str,
+    pub class_points: f64,
+    pub claiming: bool,
+    pub maiden: bool,
+}
+
+const fn race_type(
+    code: &'static str,
+    group: &'static str,
+    class_points: f64,
+    claiming: bool,
+    maiden: bool,
+) -> RaceType {
+    RaceType { code, group, class_points, claiming, maiden }
+}
+
+pub const RACE_TYPES: [RaceType; 14] = [
+    race_type("G1", "Graded stakes", 30.0, false, false),
+    race_type("G2", "Graded stakes", 24.0, false, false),
+    race_type("G3", "Graded stakes", 18.0, false, false),
+    race_type("N", "Stakes", 12.0, false, false),
+    race_type("NO", "Stakes", 8.0, false, false), // optional claiming stakes
+    race_type("A", "Allowance", 6.0, false, false),
+    race_type("AO", "Allowance", 4.0, false, false), // allowance optional claiming
+    race_type("R", "Starter", 2.0, false, false), // starter allowance
+    race_type("T", "Starter", 2.0, false, false), // starter handicap
+    race_type("S", "Maiden special weight", -2.0, false, true),
+    race_type("CO", "Claiming", 0.0, true, false), // optional claiming
+    race_type("C", "Claiming", -4.0, true, false),
+    race_type("MO", "Maiden claiming", -8.0, true, true), // maiden optional claiming
+    race_type("M", "Maiden claiming", -12.0, true, true),
+];
+
+pub fn race_type_info(code: &str) -> Option<&'static RaceType> {
+    let code = code.trim();
+    RACE_TYPES.iter().find(|race_type| race_type.code.eq_ignore_ascii_case(code))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClassMove {
+    Drop,
+    Same,
+    Rise,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimMove {
+    // Never ran for a tag before today.
+    FirstTimeClaiming,
+    // Maiden special weight last out, maiden claiming today.
+    MaidenDrop,
+    TagDrop,
+    TagRise,
+    // Ran for a tag last out and isn't today.
+    OutOfClaiming,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LineClass {
+    pub pp_id: i64,
+    pub race_date: String,
+    pub track_code: String,
+    pub race_type: String,
+    pub purse: Option<f64>,
+    pub claiming_price: Option<f64>,
+    pub level: Option<f64>,
+    pub finish_position: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HorseClass {
+    pub horse_id: i64,
+    pub program_number: String,
+    pub horse_name: String,
+    pub last_level: Option<f64>,
+    // Average of the last three rated lines.
+    pub recent_level: Option<f64>,
+    pub best_level: Option<f64>,
+    // Today's level minus the last race's.
+    pub class_change: Option<f64>,
+    pub class_move: Option<ClassMove>,
+    pub claiming_price: Option<f64>,
+    pub last_claiming_price: Option<f64>,
+    pub claim_move: Option<ClaimMove>,
+    pub claimed_last_out: bool,
+    // Newest first, as on the card.
+    pub lines: Vec<LineClass>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RaceClass {
+    pub race_number: Option<i64>,
+    pub race_type: String,
+    pub purse: Option<f64>,
+    pub claiming_price: Option<f64>,
+    pub level: Option<f64>,
+    pub bris_speed_for_class: Option<f64>,
+    pub horses: Vec<HorseClass>,
+}
+
+// Purse sets the scale, 10 points per doubling with a $10,000 purse at 0, and the race type
+// moves it within the purse: a stake outranks an allowance for the same money, which outranks
+// a claimer.
+pub fn class_level(race_type: &str, purse: Option<f64>) -> Option<f64> {
+    let purse = purse.filter(|p| *p > 0.0)?;
+    let type_points = race_type_info(race_type).map(|t| t.class_points).unwrap_or_default();
+    Some(round_tenth(10.0 * (purse / 10_000.0).log2() + type_points))
+}
+
+pub fn race_class(race: &Race) -> RaceClass {
+    let purse = race.purse.map(|p| p as f64);
+    let level = class_level(&race.race_type, purse);
+
+    RaceClass {
+        race_number: race.race_number.map(|n| n as i64),
+        race_type: race.race_type.trim().to_string(),
+        purse,
+        claiming_price: positive(race.claiming_price.map(|p| p as f64)),
+        level,
+        bris_speed_for_class: race.bris_speed_for_class.map(|v| v as f64),
+        horses: race.horses.iter().map(|horse| horse_class(race, level, horse)).collect(),
+    }
+}
+
+pub fn horse_class(race: &Race, level: Option<f64>, horse: &Horse) -> HorseClass {
+    let lines: Vec<LineClass> = horse.past_performances.iter().map(line_class).collect();
+    let levels: Vec<f64> = lines.iter().filter_map(|line| line.level).collect();
+    let last = horse.past_performances.first();
+    let last_level = lines.first().and_then(|line| line.level);
+    let recent: Vec<f64> = levels.iter().take(RECENT_LINES).copied().collect();
+
+    let class_change = level.zip(last_level).map(|(today, last)| round_tenth(today - last));
+    let class_move = class_change.map(|change| {
+        if change <= -CLASS_CHANGE_POINTS {
+            ClassMove::Drop
+        } else if change >= CLASS_CHANGE_POINTS {
+            ClassMove::Rise
+        } else {
+            ClassMove::Same
+        }
+    });
+
+    let claiming_today = is_claiming(&race.race_type);
+    let claiming_price = if claiming_today {
+        positive(horse.claiming_price_of_horse.map(|p| p as f64)).or(positive(race.claiming_price.map(|p| p as f64)))
+    } else {
+        None
+    };
+    let last_claiming_price = last
+        .filter(|pp| is_claiming(&pp.race_type))
+        .and_then(|pp| positive(pp.claiming_price.map(|p| p as f64)));
+
+    HorseClass {
+        horse_id: horse.id,
+        program_number: horse.program_number.trim().to_string(),
+        horse_name: horse.horse_name.clone(),
+        last_level,
+        recent_level: (!recent.is_empty()).then(|| round_tenth(recent.iter().sum::<f64>() / recent.len() as f64)),
+        best_level: levels.iter().copied().reduce(f64::max),
+        class_change,
+        class_move,
+        claiming_price,
+        last_claiming_price,
+        claim_move: claim_move(race, horse, claiming_price, last_claiming_price),
+        claimed_last_out: last.is_some_and(|pp| !pp.claimed_code.trim().is_empty()),
+        lines,
+    }
+}
+
+fn claim_move(
+    race: &Race,
+    horse: &Horse,
+    claiming_price: Option<f64>,
+    last_claiming_price: Option<f64>,
+) -> Option<ClaimMove> {
+    let last = horse.past_performances.first()?;
+    let last_type = last.race_type.trim().to_uppercase();
+
+    if !is_claiming(&race.race_type) {
+        return is_claiming(&last_type).then_some(ClaimMove::OutOfClaiming);
+    }
+    if is_maiden(&race.race_type) && last_type == "S" {
+        return Some(ClaimMove::MaidenDrop);
+    }
+    if !horse.past_performances.iter().any(|pp| is_claiming(&pp.race_type)) {
+        return Some(ClaimMove::FirstTimeClaiming);
+    }
+
+    let (today, last) = (claiming_price?, last_claiming_price?);
+    if today < last * (1.0 - TAG_TOLERANCE) {
+        Some(ClaimMove::TagDrop)
+    } else if today > last * (1.0 + TAG_TOLERANCE) {
+        Some(ClaimMove::TagRise)
+    } else {
+        None
+    }
+}
+
+fn line_class(pp: &PastPerformance) -> LineClass {
+    let purse = pp.purse.map(|p| p as f64);
+    LineClass {
+        pp_id: pp.id,
+        race_date: pp.race_date.clone(),
+        track_code: pp.track_code.trim().to_string(),
+        race_type: pp.race_type.trim().to_string(),
+        purse,
+        claiming_price: positive(pp.claiming_price.map(|p| p as f64)),
+        level: class_level(&pp.race_type, purse),
+        finish_position: pp.finish_position.trim().to_string(),
+    }
+}
+
+fn is_claiming(race_type: &str) -> bool {
+    race_type_info(race_type).is_some_and(|t| t.claiming)
+}
+
+fn is_maiden(race_type: &str) -> bool {
+    race_type_info(race_type).is_some_and(|t| t.maiden)
+}
+
+fn positive(value: Option<f64>) -> Option<f64> {
+    value.filter(|v| *v > 0.0)
+}
@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use crate::analysis::{distance_band, non_empty, surface_label};
+use crate::analysis::class::race_type_info;
+use crate::sqlite::pp_lines::{normalize_connection, read_pp_lines, PpLine, PpLineFilter};
+
+const BASE_WAGER: f64 = 2.0;
+const LAYOFF_LABELS: [&str; 5] = ["Under 30 days", "30-60 days", "61-180 days", "181+ days", "Debut or unknown"];
+
+// Give both names for trainer/jockey combo statistics.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ConnectionQuery {
+    pub trainer: Option<String>,
+    pub jockey: Option<String>,
+    pub track_code: Option<String>,
+    // YYYYMMDD, inclusive.
+    pub date_from: Option<String>,
+    pub date_to: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConnectionBucket {
+    pub label: String,
+    pub starts: usize,
+    pub wins: usize,
+    pub in_the_money: usize,
+    pub win_pct: f64,
+    // Starts whose $2 win return is known (a payoff or final odds for every winner).
+    pub bets: usize,
+    pub wagered: f64,
+    pub returned: f64,
+    pub roi: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConnectionReport {
+    pub trainer: Option<String>,
+    pub jockey: Option<String>,
+    pub overall: ConnectionBucket,
+    pub by_surface: Vec<ConnectionBucket>,
+    pub by_distance: Vec<ConnectionBucket>,
+    pub by_class: Vec<ConnectionBucket>,
+    pub by_layoff: Vec<ConnectionBucket>,
+    pub by_first_lasix: Vec<ConnectionBucket>,
+    pub by_equipment_change: Vec<ConnectionBucket>,
+}
+
+pub async fn connection_stats(pool: &SqlitePool, query: &ConnectionQuery) -> Result<ConnectionReport, String> {
+    let trainer = non_empty(&query.trainer);
+    let jockey = non_empty(&query.jockey);
+    if trainer.is_none() && jockey.is_none() {
+        return Err("A trainer or jockey name is required".to_string());
+    }
+
+    let filter = PpLineFilter {
+        track_code: non_empty(&query.track_code),
+        date_from: non_empty(&query.date_from),
+        date_to: non_empty(&query.date_to),
+        trainer,
+        jockey,
+        ..PpLineFilter::default()
+    };
+    let lines = read_pp_lines(pool, &filter)
+        .await
+        .map_err(|e| format!("Failed to load starts: {}", e))?;
+
+    let mut report = compute_connection_stats(&lines);
+    report.trainer = trainer.map(normalize_connection);
+    report.jockey = jockey.map(normalize_connection);
+    Ok(report)
+}
+
+pub fn compute_connection_stats(lines: &[PpLine]) -> ConnectionReport {
+    let mut overall = ConnectionBucket { label: "All starts".to_string(), ..ConnectionBucket::default() };
+    let mut by_surface = BTreeMap::new();
+    let mut by_distance = BTreeMap::new();
+    let mut by_class = BTreeMap::new();
+    let mut by_layoff: Vec<ConnectionBucket> = LAYOFF_LABELS
+        .iter()
+        .map(|label| ConnectionBucket { label: label.to_string(), ..ConnectionBucket::default() })
+        .collect();
+    let mut by_first_lasix = BTreeMap::new();
+    let mut by_equipment_change = BTreeMap::new();
+
+    for line in lines {
+        // A line without a finish is a start whose result was never entered.
+        if line.finish_position.is_none() {
+            continue;
+        }
+
+        add(&mut overall, line);
+        add(bucket(&mut by_surface, surface_label(&line.surface)), line);
+        add(bucket(&mut by_distance, line.distance_furlongs.map(distance_band).unwrap_or("Unknown")), line);
+        add(bucket(&mut by_class, class_label(&line.race_type)), line);
+        add(&mut by_layoff[layoff_group(line.days_since_last_race)], line);
+        add(bucket(&mut by_first_lasix, if line.first_lasix { "First-time Lasix" } else { "Other starts" }), line);
+        let equipment = if line.equipment_change.is_empty() { "No change" } else { line.equipment_change.as_str() };
+        add(bucket(&mut by_equipment_change, equipment), line);
+    }
+
+    let finish = |map: BTreeMap<String, ConnectionBucket>| -> Vec<ConnectionBucket> {
+        map.into_values().map(finish_bucket).collect()
+    };
+
+    ConnectionReport {
+        trainer: None,
+        jockey: None,
+        overall: finish_bucket(overall),
+        by_surface: finish(by_surface),
+        by_distance: finish(by_distance),
+        by_class: finish(by_class),
+        by_layoff: by_layoff.into_iter().map(finish_bucket).collect(),
+        by_first_lasix: finish(by_first_lasix),
+        by_equipment_change: finish(by_equipment_change),
+    }
+}
+
+fn class_label(race_type: &str) -> &'static str {
+    race_type_info(race_type).map(|t| t.group).unwrap_or("Unknown")
+}
+
+// Index into LAYOFF_LABELS.
+fn layoff_group(days: Option<i64>) -> usize {
+    match days {
+        Some(days) if days > 180 => 3,
+        Some(days) if days > 60 => 2,
+        Some(days) if days >= 30 => 1,
+        Some(days) if days > 0 => 0,
+        _ => 4,
+    }
+}
+
+fn bucket<'a>(map: &'a mut BTreeMap<String, ConnectionBucket>, label: &str) -> &'a mut ConnectionBucket {
+    map.entry(label.to_string())
+        .or_insert_with(|| ConnectionBucket { label: label.to_string(), ..ConnectionBucket::default() })
+}
+
+fn add(bucket: &mut ConnectionBucket, line: &PpLine) {
+    let finish = line.finish_position.unwrap_or_default();
+    bucket.starts += 1;
+    if finish == 1 {
+        bucket.wins += 1;
+    }
+    if (1..=3).contains(&finish) {
+        bucket.in_the_money += 1;
+    }
+
+    // The entered payoff when there is one, otherwise estimated from the final odds.
+    let returned = match finish {
+        1 => line.win_payoff.or_else(|| line.odds.map(|odds| BASE_WAGER * (odds + 1.0))),
+        _ => Some(0.0),
+    };
+    if let Some(returned) = returned {
+        bucket.bets += 1;
+        bucket.wagered += BASE_WAGER;
+        bucket.returned += returned;
+    }
+}
+
+fn finish_bucket(mut bucket: ConnectionBucket) -> ConnectionBucket {
+    if bucket.starts > 0 {
+        bucket.win_pct = bucket.wins as f64 / bucket.starts as f64;
+    }
+    if bucket.wagered > 0.0 {
+        bucket.roi = (bucket.returned - bucket.wagered) / bucket.wagered;
+    }
+    bucket
+}
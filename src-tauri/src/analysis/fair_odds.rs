@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use railbreaker_lib::contextual_speed_and_pace_model::RaceMeta;
+use railbreaker_lib::models::racecard::Race;
+use serde::{Deserialize, Serialize};
+
+// Tote odds never pay less than $2.10 on a $2 win bet.
+const MIN_TOTE_ODDS: f64 = 0.05;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FairOddsSettings {
+    // Win pool takeout, used to read tote and morning line odds as probabilities.
+    pub takeout: f64,
+    // Score points per e-fold of win probability; smaller makes the line steeper.
+    pub score_scale: f64,
+    // Odds this far above fair are an overlay, and this far below an underlay.
+    pub overlay_margin: f64,
+}
+
+impl Default for FairOddsSettings {
+    fn default() -> Self {
+        Self { takeout: 0.17, score_scale: 8.0, overlay_margin: 0.25 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OddsValue {
+    Overlay,
+    Fair,
+    Underlay,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HorseOdds {
+    pub horse_id: i64,
+    pub program_number: String,
+    pub horse_name: String,
+    pub score: Option<f64>,
+    pub probability: Option<f64>,
+    // Break-even odds-to-1 for a win bet.
+    pub fair_odds: Option<f64>,
+    // What the horse would be on the tote if the pool agreed with the model, after takeout.
+    pub line_odds: Option<f64>,
+    pub morning_line_odds: Option<f64>,
+    pub morning_line_value: Option<OddsValue>,
+    pub live_odds: Option<f64>,
+    pub live_value: Option<OddsValue>,
+    // The public's win chance at the live odds, or the morning line without them.
+    pub public_probability: Option<f64>,
+    // Expected return per $1 win bet at the live odds, or the morning line without them.
+    pub edge: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FairOddsLine {
+    pub race_number: Option<i64>,
+    pub settings: FairOddsSettings,
+    // Best chance first; unscored starters follow in program order.
+    pub horses: Vec<HorseOdds>,
+}
+
+impl FairOddsSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..1.0).contains(&self.takeout) {
+            return Err("Takeout must be between 0 and 1".to_string());
+        }
+        if !self.score_scale.is_finite() || self.score_scale <= 0.0 {
+            return Err("The score scale must be greater than zero".to_string());
+        }
+        if !self.overlay_margin.is_finite() || self.overlay_margin < 0.0 {
+            return Err("The overlay margin can't be negative".to_string());
+        }
+        Ok(())
+    }
+}
+
+// Scored starters share the win probability in proportion to exp(score / scale); scratched horses
+// are left out and unscored starters get no line.
+pub fn fair_odds_line(
+    race: &Race,
+    meta: &RaceMeta,
+    settings: &FairOddsSettings,
+    live_odds: &HashMap<String, f64>,
+) -> FairOddsLine {
+    let scores: HashMap<&str, f64> = meta
+        .race_rank_result
+        .as_ref()
+        .map(|result| {
+            result
+                .horses
+                .iter()
+                .filter_map(|h| h.score.map(|s| (h.program_number.trim(), s)))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let starters: Vec<_> = race.horses.iter().filter(|h| !h.scratched).collect();
+    let starter_scores: Vec<Option<f64>> = starters
+        .iter()
+        .map(|h| scores.get(h.program_number.trim()).copied())
+        .collect();
+    let probabilities = win_probabilities(&starter_scores, settings.score_scale);
+
+    let mut horses: Vec<HorseOdds> = starters
+        .iter()
+        .zip(probabilities)
+        .map(|(horse, probability)| {
+            let program_number = horse.program_number.trim().to_string();
+            let morning_line_odds = horse.morning_line_odds.map(|o| o as f64).filter(|o| *o > 0.0);
+            let live_odds = live_odds.get(&program_number).copied().filter(|o| *o > 0.0);
+            let price = live_odds.or(morning_line_odds);
+
+            HorseOdds {
+                horse_id: horse.id,
+                score: scores.get(program_number.as_str()).copied(),
+                probability,
+                fair_odds: probability.map(|p| 1.0 / p - 1.0),
+                line_odds: probability.map(|p| line_odds(p, settings.takeout)),
+                morning_line_odds,
+                morning_line_value: odds_value(probability, morning_line_odds, settings),
+                live_odds,
+                live_value: odds_value(probability, live_odds, settings),
+                public_probability: price.map(|odds| implied_probability(odds, settings.takeout)),
+                edge: probability.zip(price).map(|(p, odds)| p * (odds + 1.0) - 1.0),
+                horse_name: horse.horse_name.clone(),
+                program_number,
+            }
+        })
+        .collect();
+
+    horses.sort_by(|a, b| {
+        b.probability
+            .unwrap_or(-1.0)
+            .partial_cmp(&a.probability.unwrap_or(-1.0))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    FairOddsLine {
+        race_number: race.race_number.map(|n| n as i64),
+        settings: settings.clone(),
+        horses,
+    }
+}
+
+// Each scored starter's share of exp(score / scale); None for the unscored ones.
+pub fn win_probabilities(scores: &[Option<f64>], score_scale: f64) -> Vec<Option<f64>> {
+    let scale = if score_scale > 0.0 { score_scale } else { 1.0 };
+    let top = scores.iter().flatten().copied().fold(f64::MIN, f64::max);
+    let weights: Vec<Option<f64>> = scores.iter().map(|s| s.map(|s| ((s - top) / scale).exp())).collect();
+    let total: f64 = weights.iter().flatten().sum();
+
+    weights
+        .into_iter()
+        .map(|w| w.filter(|_| total > 0.0).map(|w| w / total))
+        .collect()
+}
+
+// Odds-to-1 after takeout, never below the tote's minimum payout. A heavy favourite would
+// otherwise come out at negative odds.
+pub fn line_odds(probability: f64, takeout: f64) -> f64 {
+    ((1.0 - takeout) / probability - 1.0).max(MIN_TOTE_ODDS)
+}
+
+fn odds_value(probability: Option<f64>, odds: Option<f64>, settings: &FairOddsSettings) -> Option<OddsValue> {
+    let fair = 1.0 / probability? - 1.0;
+    let odds = odds?;
+    // A sole scored starter is certain to the model, so no price can be judged against it.
+    if fair <= 0.0 {
+        return None;
+    }
+    let margin = 1.0 + settings.overlay_margin;
+    Some(if odds >= fair * margin {
+        OddsValue::Overlay
+    } else if odds * margin <= fair {
+        OddsValue::Underlay
+    } else {
+        OddsValue::Fair
+    })
+}
+
+// Win pool share the public is giving a horse at these odds.
+pub fn implied_probability(odds: f64, takeout: f64) -> f64 {
+    (1.0 - takeout) / (odds + 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{line_odds, odds_value, win_probabilities, FairOddsSettings, OddsValue};
+
+    #[test]
+    fn probabilities_sum_to_one() {
+        let probabilities = win_probabilities(&[Some(90.0), Some(84.0), None, Some(70.5)], 8.0);
+        let total: f64 = probabilities.iter().flatten().sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert_eq!(probabilities[2], None);
+        assert!(probabilities[0] > probabilities[1] && probabilities[1] > probabilities[3]);
+
+        assert!(win_probabilities(&[None, None], 8.0).iter().all(|p| p.is_none()));
+    }
+
+    #[test]
+    fn line_odds_clamp_to_tote_minimum() {
+        assert!((line_odds(0.5, 0.17) - 0.66).abs() < 1e-9);
+        assert_eq!(line_odds(0.9, 0.17), 0.05);
+        assert_eq!(line_odds(1.0, 0.17), 0.05);
+    }
+
+    #[test]
+    fn overlay_and_underlay_boundaries() {
+        let settings = FairOddsSettings::default();
+        // Fair odds at 20% are 4-1; the margin is 25%.
+        assert_eq!(odds_value(Some(0.2), Some(5.0), &settings), Some(OddsValue::Overlay));
+        assert_eq!(odds_value(Some(0.2), Some(4.99), &settings), Some(OddsValue::Fair));
+        assert_eq!(odds_value(Some(0.2), Some(3.2), &settings), Some(OddsValue::Underlay));
+        assert_eq!(odds_value(Some(0.2), Some(3.21), &settings), Some(OddsValue::Fair));
+        assert_eq!(odds_value(Some(0.2), None, &settings), None);
+        assert_eq!(odds_value(None, Some(5.0), &settings), None);
+    }
+
+    #[test]
+    fn sole_starter_has_no_value() {
+        let settings = FairOddsSettings::default();
+        assert_eq!(win_probabilities(&[Some(75.0)], 8.0), vec![Some(1.0)]);
+        assert_eq!(odds_value(Some(1.0), Some(0.4), &settings), None);
+    }
+}
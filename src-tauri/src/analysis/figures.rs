@@ -0,0 +1,257 @@
+use railbreaker_lib::models::racecard::{Horse, PastPerformance, Race};
+use serde::{Deserialize, Serialize};
+use crate::analysis::{distance_furlongs, round_tenth, ROUTE_FURLONGS};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariantMode {
+    // Figures come from the times alone.
+    Ignore,
+    // Brisnet's track variant moves each figure by `variant_weight` per point off the baseline.
+    Brisnet,
+}
+
+// A time that earns exactly the base figure at a distance.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DistancePar {
+    pub furlongs: f64,
+    pub seconds: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FigureSettings {
+    pub base_figure: f64,
+    pub lengths_per_second: f64,
+    // Figure points for a length at the reference distance; a length is worth proportionally more
+    // in shorter races and less in longer ones.
+    pub points_per_length: f64,
+    pub reference_furlongs: f64,
+    // Dirt pars, interpolated between distances. Turf uses `turf_pars` when there are any.
+    pub pars: Vec<DistancePar>,
+    pub turf_pars: Vec<DistancePar>,
+    pub variant_mode: VariantMode,
+    pub variant_baseline: f64,
+    pub variant_weight: f64,
+}
+
+impl Default for FigureSettings {
+    fn default() -> Self {
+        let par = |furlongs: f64, seconds: f64| DistancePar { furlongs, seconds };
+        Self {
+            base_figure: 100.0,
+            lengths_per_second: 5.0,
+            points_per_length: 2.2,
+            reference_furlongs: 6.0,
+            pars: vec![
+                par(2.0, 22.2),
+                par(4.0, 45.4),
+                par(4.5, 51.6),
+                par(5.0, 57.8),
+                par(5.5, 64.0),
+                par(6.0, 69.8),
+                par(6.5, 76.2),
+                par(7.0, 82.6),
+                par(8.0, 95.6),
+                par(8.5, 102.4),
+                par(9.0, 109.0),
+                par(10.0, 122.2),
+                par(12.0, 148.0),
+            ],
+            turf_pars: Vec::new(),
+            variant_mode: VariantMode::Brisnet,
+            variant_baseline: 15.0,
+            variant_weight: 0.5,
+        }
+    }
+}
+
+// Our figures for one PP line next to the Brisnet numbers for the same start. Pace figures rate
+// the horse's own time to each call, from the leader's fraction plus the lengths it was behind.
+#[derive(Debug, Clone, Serialize)]
+pub struct PpFigures {
+    pub pp_id: i64,
+    pub race_date: String,
+    pub track_code: String,
+    pub race_number: Option<i64>,
+    pub surface: String,
+    pub distance_furlongs: Option<f64>,
+    pub final_time: Option<f64>,
+    pub horse_time: Option<f64>,
+    pub variant_adjustment: f64,
+    pub speed_figure: Option<f64>,
+    pub early_pace_figure: Option<f64>,
+    pub middle_pace_figure: Option<f64>,
+    pub late_pace_figure: Option<f64>,
+    pub bris_speed_rating: Option<f64>,
+    pub speed_rating: Option<f64>,
+    // Brisnet's E1 and E2, taken at the same calls as ours.
+    pub bris_early_pace: Option<f64>,
+    pub bris_middle_pace: Option<f64>,
+    pub bris_late_pace: Option<f64>,
+    pub track_variant: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HorseFigures {
+    pub horse_id: i64,
+    pub program_number: String,
+    pub horse_name: String,
+    pub lines: Vec<PpFigures>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RaceFigures {
+    pub race_number: Option<i64>,
+    pub settings: FigureSettings,
+    pub horses: Vec<HorseFigures>,
+}
+
+impl FigureSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        let positive = [
+            ("Lengths per second", self.lengths_per_second),
+            ("Points per length", self.points_per_length),
+            ("The reference distance", self.reference_furlongs),
+        ];
+        for (name, value) in positive {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(format!("{} must be greater than zero", name));
+            }
+        }
+        if !self.base_figure.is_finite() || !self.variant_baseline.is_finite() || !self.variant_weight.is_finite() {
+            return Err("Figure settings must be numbers".to_string());
+        }
+        if self.pars.len() < 2 {
+            return Err("Figures need pars for at least two distances".to_string());
+        }
+        for pars in [&self.pars, &self.turf_pars] {
+            if pars.windows(2).any(|w| w[0].furlongs >= w[1].furlongs || w[0].seconds >= w[1].seconds) {
+                return Err("Pars must be in order of distance, each slower than the last".to_string());
+            }
+            if pars.iter().any(|p| p.furlongs <= 0.0 || p.seconds <= 0.0) {
+                return Err("Par distances and times must be greater than zero".to_string());
+            }
+        }
+        if self.turf_pars.len() == 1 {
+            return Err("Turf pars need at least two distances, or none to use the dirt pars".to_string());
+        }
+        Ok(())
+    }
+
+    // Interpolated between the nearest pars, and extended at the pace of the closest two outside them.
+    fn par_seconds(&self, surface: &str, furlongs: f64) -> Option<f64> {
+        let turf = matches!(surface.trim(), "T" | "t");
+        let pars = if turf && self.turf_pars.len() >= 2 { &self.turf_pars } else { &self.pars };
+        let idx = pars.iter().position(|p| p.furlongs >= furlongs).unwrap_or(pars.len());
+        let (low, high) = match idx {
+            0 => (pars.first()?, pars.get(1)?),
+            i if i >= pars.len() => (pars.get(pars.len().checked_sub(2)?)?, pars.last()?),
+            i => (&pars[i - 1], &pars[i]),
+        };
+        let per_furlong = (high.seconds - low.seconds) / (high.furlongs - low.furlongs);
+        Some(low.seconds + (furlongs - low.furlongs) * per_furlong)
+    }
+
+    fn points_per_second(&self, furlongs: f64) -> f64 {
+        self.points_per_length * self.reference_furlongs / furlongs * self.lengths_per_second
+    }
+
+    // Rates a time from the start, or from an earlier call, to `to` furlongs. A segment's par is the
+    // difference between the pars at its ends.
+    fn figure(&self, surface: &str, from: f64, to: f64, seconds: f64) -> Option<f64> {
+        let par = self.par_seconds(surface, to)? - if from > 0.0 { self.par_seconds(surface, from)? } else { 0.0 };
+        Some(self.base_figure + (par - seconds) * self.points_per_second(to - from))
+    }
+}
+
+pub fn race_figures(race: &Race, settings: &FigureSettings) -> RaceFigures {
+    RaceFigures {
+        race_number: race.race_number.map(|n| n as i64),
+        settings: settings.clone(),
+        horses: race.horses.iter().map(|horse| horse_figures(horse, settings)).collect(),
+    }
+}
+
+pub fn horse_figures(horse: &Horse, settings: &FigureSettings) -> HorseFigures {
+    HorseFigures {
+        horse_id: horse.id,
+        program_number: horse.program_number.trim().to_string(),
+        horse_name: horse.horse_name.clone(),
+        lines: horse.past_performances.iter().map(|pp| pp_figures(pp, settings)).collect(),
+    }
+}
+
+pub fn pp_figures(pp: &PastPerformance, settings: &FigureSettings) -> PpFigures {
+    let furlongs = distance_furlongs(pp.distance.map(|d| d as f64));
+    let route = furlongs.is_some_and(|f| f >= ROUTE_FURLONGS);
+    let (first_call, second_call) = if route { (4.0, 6.0) } else { (2.0, 4.0) };
+    let track_variant = pp.track_variant.map(|v| v as f64);
+    let variant_adjustment = match (settings.variant_mode, track_variant) {
+        (VariantMode::Brisnet, Some(variant)) => (variant - settings.variant_baseline) * settings.variant_weight,
+        _ => 0.0,
+    };
+
+    let lps = settings.lengths_per_second;
+    let horse_time = |leader: Option<f64>, beaten: Option<f64>| {
+        leader.filter(|t| *t > 0.0).map(|t| t + beaten.map(|b| b.max(0.0)).unwrap_or_default() / lps)
+    };
+    // The horse's own beaten lengths. The *_leader fields are the leader's margin over the next
+    // horse, which would charge a winner its own margin.
+    let final_time = pp.final_time.map(|t| t as f64).filter(|t| *t > 0.0);
+    let finish = horse_time(final_time, pp.finish_between_lengths.map(|b| b as f64));
+    let first = horse_time(
+        fraction_at(pp, first_call).or(pp.fraction_1.map(|t| t as f64)),
+        pp.first_call_between_lengths.map(|b| b as f64),
+    );
+    let second = horse_time(
+        fraction_at(pp, second_call).or(pp.fraction_2.map(|t| t as f64)),
+        pp.second_call_between_lengths.map(|b| b as f64),
+    );
+
+    let surface = pp.surface.as_str();
+    let rate = |from: f64, to: f64, seconds: Option<f64>| {
+        settings.figure(surface, from, to, seconds?).map(|f| round_tenth(f + variant_adjustment))
+    };
+    let speed_figure = furlongs.and_then(|f| rate(0.0, f, finish));
+    let late_pace_figure = furlongs
+        .filter(|f| *f > second_call)
+        .and_then(|f| rate(second_call, f, finish.zip(second).map(|(finish, second)| finish - second)));
+
+    PpFigures {
+        pp_id: pp.id,
+        race_date: pp.race_date.clone(),
+        track_code: pp.track_code.trim().to_string(),
+        race_number: pp.race_number.map(|n| n as i64),
+        surface: pp.surface.trim().to_string(),
+        distance_furlongs: furlongs,
+        final_time,
+        horse_time: finish.map(round_hundredth),
+        variant_adjustment: round_tenth(variant_adjustment),
+        speed_figure,
+        early_pace_figure: rate(0.0, first_call, first).filter(|_| furlongs.is_some()),
+        middle_pace_figure: rate(0.0, second_call, second).filter(|_| furlongs.is_some_and(|f| f > second_call)),
+        late_pace_figure,
+        bris_speed_rating: pp.bris_speed_rating.map(|v| v as f64),
+        speed_rating: pp.speed_rating.map(|v| v as f64),
+        bris_early_pace: if route { pp.bris_4f_pace } else { pp.bris_2f_pace }.map(|v| v as f64),
+        bris_middle_pace: if route { pp.bris_6f_pace } else { pp.bris_4f_pace }.map(|v| v as f64),
+        bris_late_pace: pp.bris_late_pace.map(|v| v as f64),
+        track_variant,
+    }
+}
+
+// The leader's time at an exact furlong pole, when Brisnet has one.
+fn fraction_at(pp: &PastPerformance, furlongs: f64) -> Option<f64> {
+    let fraction = match furlongs as i64 {
+        2 => pp.two_f_fraction,
+        4 => pp.four_f_fraction,
+        6 => pp.six_f_fraction,
+        _ => None,
+    };
+    fraction.map(|t| t as f64).filter(|t| *t > 0.0)
+}
+
+fn round_hundredth(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
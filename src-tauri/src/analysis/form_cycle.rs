@@ -0,0 +1,201 @@
+use railbreaker_lib::models::racecard::{Horse, PastPerformance, Race};
+use serde::{Deserialize, Serialize};
+
+// A start this long after the previous one is a comeback from a layoff.
+pub const LAYOFF_DAYS: i64 = 60;
+// Shorter breaks than a layoff still freshen a horse.
+pub const FRESHENING_DAYS: i64 = 45;
+// A figure this far above the previous best is a new top.
+const NEW_TOP_POINTS: f64 = 3.0;
+// Each step of an improving or regressing line moves at least this much.
+const TREND_POINTS: f64 = 2.0;
+// Lines scanned for a layoff and for earlier fresh efforts.
+const LOOKBACK_LINES: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FormPattern {
+    Improving,
+    // New top last out; bounce risk.
+    Peaked,
+    SecondOffLayoff,
+    ThirdOffLayoff,
+    Regressing,
+    // Returning from a freshening today.
+    Freshened,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FormLabel {
+    pub pattern: FormPattern,
+    pub evidence: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FormLine {
+    pub race_date: String,
+    pub days_since_last_race: Option<i64>,
+    pub speed_rating: Option<f64>,
+    pub finish_position: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HorseForm {
+    pub horse_id: i64,
+    pub program_number: String,
+    pub horse_name: String,
+    pub days_off: Option<i64>,
+    pub labels: Vec<FormLabel>,
+    // Newest first, the lines the labels were read from.
+    pub lines: Vec<FormLine>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RaceForm {
+    pub race_number: Option<i64>,
+    pub horses: Vec<HorseForm>,
+}
+
+pub fn race_form_cycles(race: &Race) -> RaceForm {
+    RaceForm {
+        race_number: race.race_number.map(|n| n as i64),
+        horses: race.horses.iter().map(horse_form_cycle).collect(),
+    }
+}
+
+pub fn horse_form_cycle(horse: &Horse) -> HorseForm {
+    let lines: Vec<FormLine> = horse
+        .past_performances
+        .iter()
+        .filter(|pp| !pp.race_date.trim().is_empty())
+        .take(LOOKBACK_LINES)
+        .map(form_line)
+        .collect();
+    let days_off = horse.days_since_last_race.map(|d| d as i64);
+
+    let mut labels = Vec::new();
+    labels.extend(figure_trend(&lines));
+    labels.extend(new_top(&lines));
+    labels.extend(off_layoff(days_off, &lines));
+    labels.extend(freshened(days_off, &lines));
+
+    HorseForm {
+        horse_id: horse.id,
+        program_number: horse.program_number.trim().to_string(),
+        horse_name: horse.horse_name.clone(),
+        days_off,
+        labels,
+        lines,
+    }
+}
+
+// Three rated starts in a row, each better (or worse) than the one before.
+fn figure_trend(lines: &[FormLine]) -> Option<FormLabel> {
+    let figures: Vec<f64> = lines.iter().filter_map(|line| line.speed_rating).take(3).collect();
+    let [last, middle, first] = figures[..] else {
+        return None;
+    };
+    let evidence = format!("Figures {:.0}, {:.0}, {:.0}", first, middle, last);
+
+    if middle - first >= TREND_POINTS && last - middle >= TREND_POINTS {
+        Some(FormLabel { pattern: FormPattern::Improving, evidence })
+    } else if first - middle >= TREND_POINTS && middle - last >= TREND_POINTS {
+        Some(FormLabel { pattern: FormPattern::Regressing, evidence })
+    } else {
+        None
+    }
+}
+
+// Last out beat everything before it by a clear margin; it needs at least two earlier figures.
+fn new_top(lines: &[FormLine]) -> Option<FormLabel> {
+    let last = lines.first()?.speed_rating?;
+    let earlier: Vec<f64> = lines[1..].iter().filter_map(|line| line.speed_rating).collect();
+    if earlier.len() < 2 {
+        return None;
+    }
+    let best = earlier.iter().copied().fold(f64::MIN, f64::max);
+    (last - best >= NEW_TOP_POINTS).then(|| FormLabel {
+        pattern: FormPattern::Peaked,
+        evidence: format!("New top {:.0} last out, previous best {:.0}", last, best),
+    })
+}
+
+// Today is the second start back when the last start was the comeback, the third when the one
+// before it was. A horse laid off again since is first back instead, which Freshened covers.
+fn off_layoff(days_off: Option<i64>, lines: &[FormLine]) -> Option<FormLabel> {
+    if days_off.is_some_and(|d| d >= LAYOFF_DAYS) {
+        return None;
+    }
+    let comeback = lines
+        .iter()
+        .position(|line| line.days_since_last_race.is_some_and(|d| d >= LAYOFF_DAYS))?;
+    let pattern = match comeback {
+        0 => FormPattern::SecondOffLayoff,
+        1 => FormPattern::ThirdOffLayoff,
+        _ => return None,
+    };
+
+    let line = &lines[comeback];
+    let mut evidence = format!(
+        "Back from {} days on {}",
+        line.days_since_last_race.unwrap_or_default(),
+        line.race_date.trim()
+    );
+    let figures: Vec<String> = lines[..=comeback]
+        .iter()
+        .rev()
+        .map(|line| line.speed_rating.map(|f| format!("{:.0}", f)).unwrap_or_else(|| "-".to_string()))
+        .collect();
+    evidence.push_str(&format!(", figures since {}", figures.join(", ")));
+
+    Some(FormLabel { pattern, evidence })
+}
+
+// Today's break is a freshening; the evidence is how the horse has run fresh before.
+fn freshened(days_off: Option<i64>, lines: &[FormLine]) -> Option<FormLabel> {
+    let days_off = days_off.filter(|d| *d >= FRESHENING_DAYS)?;
+    if lines.is_empty() {
+        return None;
+    }
+
+    let fresh: Vec<&FormLine> = lines
+        .iter()
+        .filter(|line| line.days_since_last_race.is_some_and(|d| d >= FRESHENING_DAYS))
+        .collect();
+    let in_the_money = fresh.iter().filter(|line| finish(line).is_some_and(|f| f <= 3)).count();
+    let evidence = if fresh.is_empty() {
+        format!("{} days off, no fresh starts in the last {}", days_off, lines.len())
+    } else {
+        format!("{} days off, in the money {} of {} times fresh", days_off, in_the_money, fresh.len())
+    };
+
+    Some(FormLabel { pattern: FormPattern::Freshened, evidence })
+}
+
+fn form_line(pp: &PastPerformance) -> FormLine {
+    FormLine {
+        race_date: pp.race_date.clone(),
+        days_since_last_race: pp.days_since_last_race.map(|d| d as i64),
+        speed_rating: pp
+            .bris_speed_rating
+            .or(pp.speed_rating)
+            .map(|f| f as f64)
+            .filter(|f| *f > 0.0),
+        finish_position: pp.finish_position.trim().to_string(),
+    }
+}
+
+fn finish(line: &FormLine) -> Option<i64> {
+    let digits: String = line.finish_position.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok().filter(|position| *position > 0)
+}
+
+pub fn form_pattern_label(pattern: FormPattern) -> &'static str {
+    match pattern {
+        FormPattern::Improving => "Improving",
+        FormPattern::Peaked => "Peaked / bounce risk",
+        FormPattern::SecondOffLayoff => "2nd off layoff",
+        FormPattern::ThirdOffLayoff => "3rd off layoff",
+        FormPattern::Regressing => "Regressing",
+        FormPattern::Freshened => "Freshened",
+    }
+}
@@ -0,0 +1,74 @@
+pub mod backtest;
+pub mod bias;
+pub mod class;
+pub mod connections;
+pub mod fair_odds;
+pub mod figures;
+pub mod form_cycle;
+pub mod model_parameters;
+pub mod trip_model;
+pub mod workouts;
+
+use railbreaker_lib::contextual_speed_and_pace_model::{HorseRank, RaceMeta};
+use serde::Serialize;
+
+pub const YARDS_PER_FURLONG: f64 = 220.0;
+// Races this long or longer are routes. Brisnet calls them at 4f and 6f rather than 2f and 4f.
+pub const ROUTE_FURLONGS: f64 = 8.0;
+
+// Brisnet distances are in yards, negative when the race was run at "about" the distance.
+pub fn distance_furlongs(distance_yards: Option<f64>) -> Option<f64> {
+    distance_yards
+        .filter(|d| *d != 0.0)
+        .map(|d| d.abs() / YARDS_PER_FURLONG)
+}
+
+pub fn distance_band(furlongs: f64) -> &'static str {
+    if furlongs < 6.5 {
+        "Sprint (< 6.5f)"
+    } else if furlongs < ROUTE_FURLONGS {
+        "Sprint (6.5f - 7.5f)"
+    } else if furlongs < 9.0 {
+        "Route (8f - 8.5f)"
+    } else {
+        "Route (9f+)"
+    }
+}
+
+pub fn surface_label(surface: &str) -> &'static str {
+    match surface.trim() {
+        "D" | "d" => "Dirt",
+        "T" | "t" => "Turf",
+        _ => "Unknown",
+    }
+}
+
+// Highest model score first. Horses the model left unscored are not included; callers that list
+// the whole field print them after, in program order.
+pub fn ranked_horses(meta: &RaceMeta) -> Vec<&HorseRank> {
+    let mut horses: Vec<&HorseRank> = meta
+        .race_rank_result
+        .as_ref()
+        .map(|result| result.horses.iter().filter(|h| h.score.is_some()).collect())
+        .unwrap_or_default();
+    horses.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    horses
+}
+
+pub fn round_tenth(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+// A trimmed optional field, None when it is blank.
+pub fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+// Model enums serialize as their variant names, which are also what the frontend displays.
+pub fn enum_label<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(label)) => label,
+        Ok(other) => other.to_string(),
+        Err(_) => "Unknown".to_string(),
+    }
+}
@@ -0,0 +1,125 @@
+use railbreaker_lib::contextual_speed_and_pace_model::{derive_race_meta, RaceMeta};
+use railbreaker_lib::models::racecard::Race;
+use serde::{Deserialize, Serialize};
+
+// Named profiles plus which one applies to each surface and distance. They are kept in config.json
+// for the library to apply once derive_race_meta accepts them; see rank_race_with_parameters.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelParameters {
+    pub profiles: Vec<ModelProfile>,
+    pub dirt_sprint: Option<String>,
+    pub dirt_route: Option<String>,
+    pub turf_sprint: Option<String>,
+    pub turf_route: Option<String>,
+}
+
+// Every group is optional; a group left out keeps the library's value for it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelProfile {
+    pub name: String,
+    // Pace heat at which the shape becomes Honest, Fast and Meltdown.
+    pub pace_heat_thresholds: Option<[f64; 3]>,
+    // EPI below the first cutoff cools the shape one step, above the second heats it one step.
+    pub epi_cutoffs: Option<[f64; 2]>,
+    pub weights: Option<ScoreWeights>,
+    pub confidence: Option<ConfidenceThresholds>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoreWeights {
+    pub speed: f64,
+    pub early: f64,
+    pub late: f64,
+    // Scales the workout signal score.
+    pub workout_bonus: f64,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        Self { speed: 1.0, early: 1.0, late: 1.0, workout_bonus: 1.0 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfidenceThresholds {
+    // Minimum gap between the top two scores.
+    pub strong_single_gap: f64,
+    pub playable_gap: f64,
+    // Races with a smaller top-to-fourth spread are wide open.
+    pub wide_open_spread: f64,
+}
+
+impl Default for ConfidenceThresholds {
+    fn default() -> Self {
+        Self { strong_single_gap: 6.0, playable_gap: 3.0, wide_open_spread: 4.0 }
+    }
+}
+
+impl ModelParameters {
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, profile) in self.profiles.iter().enumerate() {
+            if profile.name.trim().is_empty() {
+                return Err("Every model profile needs a name".to_string());
+            }
+            if self.profiles[..i].iter().any(|p| p.name == profile.name) {
+                return Err(format!("Duplicate model profile name: {}", profile.name));
+            }
+            profile.validate()?;
+        }
+
+        let slots = [
+            ("dirt sprints", &self.dirt_sprint),
+            ("dirt routes", &self.dirt_route),
+            ("turf sprints", &self.turf_sprint),
+            ("turf routes", &self.turf_route),
+        ];
+        for (slot, name) in slots {
+            if let Some(name) = name {
+                if !self.profiles.iter().any(|p| &p.name == name) {
+                    return Err(format!("Unknown model profile for {}: {}", slot, name));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ModelProfile {
+    fn validate(&self) -> Result<(), String> {
+        if let Some(t) = self.pace_heat_thresholds {
+            if !(t[0] <= t[1] && t[1] <= t[2]) {
+                return Err(format!("Pace heat thresholds must be ascending in profile {}", self.name));
+            }
+        }
+        if let Some(c) = self.epi_cutoffs {
+            if c[0] > c[1] {
+                return Err(format!("EPI cutoffs must be ascending in profile {}", self.name));
+            }
+        }
+        if let Some(w) = &self.weights {
+            if [w.speed, w.early, w.late, w.workout_bonus].iter().any(|v| !v.is_finite()) {
+                return Err(format!("Score weights must be numbers in profile {}", self.name));
+            }
+        }
+        if let Some(c) = &self.confidence {
+            if c.playable_gap > c.strong_single_gap {
+                return Err(format!(
+                    "The playable gap can't exceed the strong single gap in profile {}",
+                    self.name
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+// The ranking every caller goes through. derive_race_meta in railbreaker-lib takes no parameters
+// yet, so the profiles are stored, assigned and validated here but the result is the library's
+// own. Re-scoring its output instead would present numbers that are not the model's.
+pub fn rank_race_with_parameters(race: &Race, date: Option<&str>, _parameters: &ModelParameters) -> RaceMeta {
+    derive_race_meta(race, date)
+}
@@ -0,0 +1,375 @@
+use railbreaker_lib::models::racecard::{Horse, PastPerformance, Race};
+use serde::{Deserialize, Serialize};
+use crate::sqlite::pp_lines::pp_date;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TroubleType {
+    SlowStart,
+    Stumbled,
+    Bumped,
+    Checked,
+    Steadied,
+    Blocked,
+    Wide,
+    Erratic,
+    PaceDuel,
+    Eased,
+    Tired,
+    Closed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Severity {
+    Minor,
+    Moderate,
+    Major,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CallPoint {
+    Start,
+    Early,
+    Turn,
+    Stretch,
+    Unknown,
+}
+
+// Good and bad describe the effort itself; excusable trouble explains a poor result away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TripOutlook {
+    Good,
+    Bad,
+    Excusable,
+    Neutral,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TripWeights {
+    // Points for the most recent race, the one before it, and so on; the length is how many
+    // races the model looks back.
+    pub race_weights: Vec<f64>,
+    // Races run longer ago than this before the card are ignored.
+    pub max_days_back: i64,
+    pub good: f64,
+    pub bad: f64,
+    pub excusable: f64,
+    pub minor: f64,
+    pub moderate: f64,
+    pub major: f64,
+}
+
+impl Default for TripWeights {
+    fn default() -> Self {
+        Self {
+            race_weights: vec![50.0, 30.0, 10.0],
+            max_days_back: 60,
+            good: 1.0,
+            bad: -1.0,
+            // An excused effort is a horse to use back, and the severity factor makes the
+            // worse the trouble the bigger the excuse.
+            excusable: 0.75,
+            minor: 0.5,
+            moderate: 1.0,
+            major: 1.5,
+        }
+    }
+}
+
+impl TripWeights {
+    fn outlook(&self, outlook: TripOutlook) -> f64 {
+        match outlook {
+            TripOutlook::Good => self.good,
+            TripOutlook::Bad => self.bad,
+            TripOutlook::Excusable => self.excusable,
+            TripOutlook::Neutral => 0.0,
+        }
+    }
+
+    fn severity(&self, severity: Severity) -> f64 {
+        match severity {
+            Severity::Minor => self.minor,
+            Severity::Moderate => self.moderate,
+            Severity::Major => self.major,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TripEvent {
+    pub trouble: TroubleType,
+    pub severity: Severity,
+    pub call_point: CallPoint,
+    pub outlook: TripOutlook,
+    // The comment phrase the event was read from.
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TripLine {
+    pub race_date: String,
+    pub track_code: String,
+    pub days_back: Option<i64>,
+    pub comment: String,
+    pub events: Vec<TripEvent>,
+    pub outlook: TripOutlook,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HorseTrip {
+    pub horse_id: i64,
+    pub program_number: String,
+    pub horse_name: String,
+    pub scratched: bool,
+    pub score: f64,
+    pub trips: Vec<TripLine>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TripRanking {
+    pub race_number: Option<i64>,
+    pub weights: TripWeights,
+    // Highest score first.
+    pub horses: Vec<HorseTrip>,
+}
+
+pub fn rank_race_trips(race: &Race, racecard_date: Option<&str>, weights: &TripWeights) -> TripRanking {
+    let mut horses: Vec<HorseTrip> = race
+        .horses
+        .iter()
+        .map(|horse| score_horse(horse, racecard_date, weights))
+        .collect();
+    horses.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+
+    TripRanking {
+        race_number: race.race_number.map(|n| n as i64),
+        weights: weights.clone(),
+        horses,
+    }
+}
+
+pub fn score_horse(horse: &Horse, racecard_date: Option<&str>, weights: &TripWeights) -> HorseTrip {
+    let card_date = racecard_date.and_then(|date| chrono::NaiveDate::parse_from_str(date, "%Y%m%d").ok());
+
+    let mut trips = Vec::new();
+    for (pp, race_weight) in horse.past_performances.iter().zip(&weights.race_weights) {
+        let race_date = pp_date(&pp.race_date).unwrap_or_default();
+        let days_back = card_date
+            .zip(chrono::NaiveDate::parse_from_str(&race_date, "%Y%m%d").ok())
+            .map(|(card, raced)| (card - raced).num_days());
+        if days_back.is_some_and(|days| days > weights.max_days_back) {
+            break;
+        }
+
+        let events = parse_trip(pp);
+        let (outlook, severity) = race_outlook(&events);
+        let score = match severity {
+            Some(severity) => race_weight * weights.outlook(outlook) * weights.severity(severity),
+            None => 0.0,
+        };
+
+        trips.push(TripLine {
+            race_date,
+            track_code: pp.track_code.trim().to_string(),
+            days_back,
+            comment: trip_text(pp),
+            events,
+            outlook,
+            score,
+        });
+    }
+
+    HorseTrip {
+        horse_id: horse.id,
+        program_number: horse.program_number.clone(),
+        horse_name: horse.horse_name.clone(),
+        scratched: horse.scratched,
+        score: trips.iter().map(|t| t.score).sum(),
+        trips,
+    }
+}
+
+// Trouble excuses a bad effort, and trouble overcome adds to a good one. When a race shows both
+// a good and a bad effort the more severe one decides.
+fn race_outlook(events: &[TripEvent]) -> (TripOutlook, Option<Severity>) {
+    let worst = |outlook: TripOutlook| events.iter().filter(|e| e.outlook == outlook).map(|e| e.severity).max();
+    let (good, bad, excuse) = (worst(TripOutlook::Good), worst(TripOutlook::Bad), worst(TripOutlook::Excusable));
+
+    match (good, bad, excuse) {
+        (_, Some(_), Some(excuse)) => (TripOutlook::Excusable, Some(excuse)),
+        (Some(good), None, excuse) => (TripOutlook::Good, Some(good.max(excuse.unwrap_or(good)))),
+        (Some(good), Some(bad), None) if good > bad => (TripOutlook::Good, Some(good)),
+        (_, Some(bad), None) => (TripOutlook::Bad, Some(bad)),
+        (None, None, Some(excuse)) => (TripOutlook::Excusable, Some(excuse)),
+        (None, None, None) => (TripOutlook::Neutral, None),
+    }
+}
+
+// The trip comment and Brisnet's extended start comment, without the date the latter leads with.
+pub fn trip_text(pp: &PastPerformance) -> String {
+    let extended = strip_leading_date(&pp.extended_start_comment);
+    [pp.trip_comment.trim(), extended]
+        .into_iter()
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+pub fn parse_trip(pp: &PastPerformance) -> Vec<TripEvent> {
+    let mut events: Vec<TripEvent> = Vec::new();
+
+    for phrase in trip_text(pp).split([',', ';']).map(str::trim).filter(|p| !p.is_empty()) {
+        for event in parse_phrase(phrase) {
+            // Both comments often describe the same trouble.
+            let duplicate = events
+                .iter_mut()
+                .find(|e| e.trouble == event.trouble && e.call_point == event.call_point);
+            match duplicate {
+                Some(existing) => existing.severity = existing.severity.max(event.severity),
+                None => events.push(event),
+            }
+        }
+    }
+
+    events
+}
+
+pub fn parse_phrase(phrase: &str) -> Vec<TripEvent> {
+    let lower = phrase.to_lowercase();
+    let tokens: Vec<&str> = lower
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '/'))
+        .filter(|t| !t.is_empty())
+        .collect();
+    let has = |prefixes: &[&str]| tokens.iter().any(|t| prefixes.iter().any(|p| t.starts_with(p)));
+    let has_phrase = |phrases: &[&str]| phrases.iter().any(|p| lower.contains(p));
+    let negated = tokens.iter().any(|t| ["no", "not", "never", "failed"].contains(t));
+
+    let mut found = Vec::new();
+    if has(&["slow", "dwelt", "awk", "hesitat", "unprepared"]) || has_phrase(&["broke in", "broke out", "off poorly"]) {
+        found.push(TroubleType::SlowStart);
+    }
+    if has(&["stumbl", "stmbl", "bobbl", "bobl"]) {
+        found.push(TroubleType::Stumbled);
+    }
+    if has(&["bump", "bmp", "brush", "jostl", "sideswip"]) {
+        found.push(TroubleType::Bumped);
+    }
+    if has(&["check", "chk", "clipped"]) {
+        found.push(TroubleType::Checked);
+    }
+    if has(&["stead", "stdy", "altered"]) {
+        found.push(TroubleType::Steadied);
+    }
+    if has(&["block", "blkd", "boxed", "shuffl", "tight", "pinch"]) || has_phrase(&["no room", "shut off", "waited"]) {
+        found.push(TroubleType::Blocked);
+    }
+    if has(&["lug", "bore", "drift", "duck", "erratic", "swerv", "veer"]) {
+        found.push(TroubleType::Erratic);
+    }
+    if has(&["duel", "vied", "pressured"]) {
+        found.push(TroubleType::PaceDuel);
+    }
+    if has(&["eased", "lame", "distanced"]) || has_phrase(&["pulled up", "broke down", "lost rider"]) {
+        found.push(TroubleType::Eased);
+    }
+    let closed = has(&["rall", "clos", "gain", "drew", "driving", "clear", "surg", "kicked"]) || has_phrase(&["going away", "finished well", "held on"]);
+    if has(&["tire", "fade", "weaken", "stopp", "empty", "wilt", "outrun", "retreat", "gave"]) || (closed && negated) {
+        found.push(TroubleType::Tired);
+    } else if closed {
+        found.push(TroubleType::Closed);
+    }
+
+    let wide = wide_path(&tokens);
+    if wide.is_some() {
+        found.push(TroubleType::Wide);
+    }
+
+    let call_point = call_point(&tokens);
+    let intensity = if has(&["bad", "hard", "sever", "sharp", "nearly", "violent", "clipped"]) || has_phrase(&["lost rider", "broke down"]) {
+        Some(Severity::Major)
+    } else if has(&["slight", "brief", "mild", "light", "brush", "some"]) {
+        Some(Severity::Minor)
+    } else {
+        None
+    };
+
+    found
+        .into_iter()
+        .map(|trouble| {
+            let severity = match trouble {
+                TroubleType::Wide => wide.unwrap_or(Severity::Minor),
+                TroubleType::Eased => Severity::Major,
+                TroubleType::PaceDuel => intensity.unwrap_or(Severity::Minor),
+                _ => intensity.unwrap_or(Severity::Moderate),
+            };
+            let call_point = match (trouble, call_point) {
+                (TroubleType::SlowStart | TroubleType::Stumbled, CallPoint::Unknown) => CallPoint::Start,
+                (TroubleType::Tired | TroubleType::Closed, CallPoint::Unknown) => CallPoint::Stretch,
+                (_, point) => point,
+            };
+            let outlook = match trouble {
+                TroubleType::Tired => TripOutlook::Bad,
+                TroubleType::Closed => TripOutlook::Good,
+                _ => TripOutlook::Excusable,
+            };
+
+            TripEvent { trouble, severity, call_point, outlook, text: phrase.to_string() }
+        })
+        .collect()
+}
+
+// "4w", "5wd", "3-4w" or plain "wide". Two wide or closer is ordinary ground and not trouble.
+fn wide_path(tokens: &[&str]) -> Option<Severity> {
+    let path = tokens
+        .iter()
+        .filter(|t| t.starts_with(|c: char| c.is_ascii_digit()))
+        .filter_map(|t| {
+            let (digits, rest) = t.split_at(t.find(|c: char| !c.is_ascii_digit())?);
+            if matches!(rest, "w" | "wd" | "wide" | "p" | "path") {
+                digits.parse::<i64>().ok()
+            } else {
+                None
+            }
+        })
+        .max();
+
+    match path {
+        Some(path) if path >= 5 => Some(Severity::Major),
+        Some(4) => Some(Severity::Moderate),
+        Some(3) => Some(Severity::Minor),
+        Some(_) => None,
+        None if tokens.iter().any(|t| *t == "widest" || *t == "vwide") => Some(Severity::Major),
+        None if tokens.iter().any(|t| *t == "wide" || *t == "wd") => Some(Severity::Minor),
+        None => None,
+    }
+}
+
+// "1st turn" is early in the race, so it is checked before a bare "turn".
+fn call_point(tokens: &[&str]) -> CallPoint {
+    let any = |words: &[&str]| tokens.iter().any(|t| words.contains(t));
+    if any(&["st", "start", "brk", "break", "gate", "leaving", "bell"]) {
+        CallPoint::Start
+    } else if any(&["1st", "first", "early", "1/2", "5/8", "backstretch", "bkstr", "clubhouse"]) {
+        CallPoint::Early
+    } else if any(&["str", "stretch", "lane", "1/8", "1/16", "late", "deep", "wire", "final"]) {
+        CallPoint::Stretch
+    } else if any(&["turn", "far", "2nd", "3/8", "1/4", "5/16", "bend"]) {
+        CallPoint::Turn
+    } else {
+        CallPoint::Unknown
+    }
+}
+
+fn strip_leading_date(comment: &str) -> &str {
+    let comment = comment.trim();
+    let first = comment.split_whitespace().next().unwrap_or_default();
+    let date = first.trim_end_matches(':');
+    let separators = date.chars().filter(|c| *c == '/' || *c == '-').count();
+    if separators == 2 && date.chars().all(|c| c.is_ascii_digit() || c == '/' || c == '-') {
+        comment[first.len()..].trim_start_matches([' ', ':', '-']).trim()
+    } else {
+        comment
+    }
+}
@@ -0,0 +1,254 @@
+use chrono::NaiveDate;
+use railbreaker_lib::models::racecard::{Horse, Race, Workout};
+use serde::{Deserialize, Serialize};
+use crate::analysis::{distance_furlongs, round_tenth};
+use crate::analysis::form_cycle::LAYOFF_DAYS;
+use crate::sqlite::pp_lines::pp_date;
+
+// Works within this many days of the race count as recent.
+const RECENT_DAYS: i64 = 14;
+// A recent work at or above this percentile is a sharp drill.
+const SHARP_PERCENTILE: f64 = 75.0;
+// Gaps between the latest works, at least this many, each within the tolerance of their average.
+const SPACING_GAPS: usize = 3;
+const SPACING_TOLERANCE_DAYS: f64 = 2.0;
+// Works since the last start that make a layoff horse's tab a serious one.
+const LAYOFF_TAB_WORKS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WorkoutPattern {
+    // Fastest of the day at the distance.
+    Bullet,
+    RegularSpacing,
+    GateWork,
+    // Coming off a layoff with a full set of works since the last start.
+    LayoffTab,
+    SharpRecent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum WorkoutSort {
+    #[default]
+    ProgramNumber,
+    BestPercentile,
+    AveragePercentile,
+    LatestWork,
+    WorkCount,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct WorkoutQuery {
+    pub sort: WorkoutSort,
+    // Only horses showing this pattern.
+    pub pattern: Option<WorkoutPattern>,
+    // Only horses with a work at least this good.
+    pub min_percentile: Option<f64>,
+    // Only works this close to the race; all works when omitted.
+    pub within_days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkoutLine {
+    pub date: String,
+    pub days_before_race: Option<i64>,
+    pub track: String,
+    pub distance_furlongs: Option<f64>,
+    pub time: Option<f64>,
+    pub seconds_per_furlong: Option<f64>,
+    pub condition: String,
+    pub description: String,
+    pub training_track: String,
+    pub rank: Option<i64>,
+    pub workers: Option<i64>,
+    // 100 is the fastest of the day at the distance, 0 the slowest.
+    pub percentile: Option<f64>,
+    pub bullet: bool,
+    pub gate: bool,
+    pub since_last_race: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HorseWorkouts {
+    pub horse_id: i64,
+    pub program_number: String,
+    pub horse_name: String,
+    pub work_count: usize,
+    pub bullets: usize,
+    pub gate_works: usize,
+    pub works_since_last_race: usize,
+    pub best_percentile: Option<f64>,
+    pub average_percentile: Option<f64>,
+    // Average days between the latest works.
+    pub spacing_days: Option<f64>,
+    pub latest_work_days: Option<i64>,
+    pub patterns: Vec<WorkoutPattern>,
+    // Newest first.
+    pub works: Vec<WorkoutLine>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RaceWorkouts {
+    pub race_number: Option<i64>,
+    pub horses: Vec<HorseWorkouts>,
+}
+
+pub fn race_workouts(race: &Race, racecard_date: Option<&str>, query: &WorkoutQuery) -> RaceWorkouts {
+    let card_date = racecard_date.and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok());
+
+    let mut horses: Vec<HorseWorkouts> = race
+        .horses
+        .iter()
+        .map(|horse| horse_workouts(horse, card_date, query.within_days))
+        .filter(|horse| query.pattern.is_none_or(|pattern| horse.patterns.contains(&pattern)))
+        .filter(|horse| {
+            query
+                .min_percentile
+                .is_none_or(|min| horse.best_percentile.is_some_and(|best| best >= min))
+        })
+        .collect();
+
+    match query.sort {
+        WorkoutSort::ProgramNumber => {}
+        WorkoutSort::BestPercentile => horses.sort_by(|a, b| descending(a.best_percentile, b.best_percentile)),
+        WorkoutSort::AveragePercentile => {
+            horses.sort_by(|a, b| descending(a.average_percentile, b.average_percentile))
+        }
+        WorkoutSort::LatestWork => horses.sort_by_key(|h| h.latest_work_days.unwrap_or(i64::MAX)),
+        WorkoutSort::WorkCount => horses.sort_by_key(|h| std::cmp::Reverse(h.work_count)),
+    }
+
+    RaceWorkouts {
+        race_number: race.race_number.map(|n| n as i64),
+        horses,
+    }
+}
+
+pub fn horse_workouts(horse: &Horse, card_date: Option<NaiveDate>, within_days: Option<i64>) -> HorseWorkouts {
+    let last_race = horse
+        .past_performances
+        .first()
+        .and_then(|pp| parse_date(&pp.race_date));
+
+    let mut works: Vec<WorkoutLine> = horse
+        .workouts
+        .iter()
+        .filter(|w| !w.date.trim().is_empty())
+        .map(|w| workout_line(w, card_date, last_race))
+        .filter(|w| within_days.is_none_or(|days| w.days_before_race.is_some_and(|d| d <= days)))
+        .collect();
+    works.sort_by_key(|w| w.days_before_race.unwrap_or(i64::MAX));
+
+    let percentiles: Vec<f64> = works.iter().filter_map(|w| w.percentile).collect();
+    let spacing = spacing_days(&works);
+    let works_since_last_race = works.iter().filter(|w| w.since_last_race).count();
+    let days_off = horse.days_since_last_race.map(|d| d as i64);
+
+    let mut patterns = Vec::new();
+    if works.iter().any(|w| w.bullet) {
+        patterns.push(WorkoutPattern::Bullet);
+    }
+    if spacing.is_some() {
+        patterns.push(WorkoutPattern::RegularSpacing);
+    }
+    if works.iter().any(|w| w.gate) {
+        patterns.push(WorkoutPattern::GateWork);
+    }
+    if days_off.is_some_and(|d| d >= LAYOFF_DAYS) && works_since_last_race >= LAYOFF_TAB_WORKS {
+        patterns.push(WorkoutPattern::LayoffTab);
+    }
+    let sharp = works.iter().any(|w| {
+        w.days_before_race.is_some_and(|d| d <= RECENT_DAYS)
+            && (w.bullet || w.percentile.is_some_and(|p| p >= SHARP_PERCENTILE))
+    });
+    if sharp {
+        patterns.push(WorkoutPattern::SharpRecent);
+    }
+
+    HorseWorkouts {
+        horse_id: horse.id,
+        program_number: horse.program_number.trim().to_string(),
+        horse_name: horse.horse_name.clone(),
+        work_count: works.len(),
+        bullets: works.iter().filter(|w| w.bullet).count(),
+        gate_works: works.iter().filter(|w| w.gate).count(),
+        works_since_last_race,
+        best_percentile: percentiles.iter().copied().reduce(f64::max),
+        average_percentile: (!percentiles.is_empty())
+            .then(|| round_tenth(percentiles.iter().sum::<f64>() / percentiles.len() as f64)),
+        spacing_days: spacing,
+        latest_work_days: works.first().and_then(|w| w.days_before_race),
+        patterns,
+        works,
+    }
+}
+
+// Brisnet marks a bullet with rank 1 and also with a negative time.
+fn workout_line(workout: &Workout, card_date: Option<NaiveDate>, last_race: Option<NaiveDate>) -> WorkoutLine {
+    let date = parse_date(&workout.date);
+    let raw_time = workout.time.map(|t| t as f64).filter(|t| *t != 0.0);
+    let time = raw_time.map(f64::abs);
+    let distance = distance_furlongs(workout.distance.map(|d| d as f64));
+    let rank = workout.rank.map(|r| r as i64).filter(|r| *r > 0);
+    let workers = workout.workouts_that_day_distance.map(|n| n as i64).filter(|n| *n > 0);
+    let description = workout.description.trim().to_string();
+
+    WorkoutLine {
+        date: workout.date.clone(),
+        days_before_race: card_date.zip(date).map(|(card, worked)| (card - worked).num_days()),
+        track: workout.track.trim().to_string(),
+        distance_furlongs: distance,
+        time,
+        seconds_per_furlong: time.zip(distance).map(|(t, d)| (t / d * 100.0).round() / 100.0),
+        condition: workout.condition.trim().to_string(),
+        gate: is_gate_work(&description),
+        description,
+        training_track: workout.main_inner_track_indicator.trim().to_string(),
+        rank,
+        workers,
+        percentile: percentile(rank, workers),
+        bullet: rank == Some(1) || raw_time.is_some_and(|t| t < 0.0),
+        since_last_race: date.zip(last_race).is_some_and(|(worked, raced)| worked > raced),
+    }
+}
+
+// A lone worker at the distance is both the fastest and the slowest, and counts as the fastest.
+fn percentile(rank: Option<i64>, workers: Option<i64>) -> Option<f64> {
+    let (rank, workers) = (rank?, workers?);
+    if rank > workers {
+        return None;
+    }
+    if workers == 1 {
+        return Some(100.0);
+    }
+    Some(round_tenth((workers - rank) as f64 / (workers - 1) as f64 * 100.0))
+}
+
+// The average gap when the latest works come at a steady interval.
+fn spacing_days(works: &[WorkoutLine]) -> Option<f64> {
+    let days: Vec<i64> = works.iter().filter_map(|w| w.days_before_race).collect();
+    let gaps: Vec<f64> = days.windows(2).take(SPACING_GAPS + 2).map(|w| (w[1] - w[0]) as f64).collect();
+    if gaps.len() < SPACING_GAPS {
+        return None;
+    }
+
+    let average = gaps.iter().sum::<f64>() / gaps.len() as f64;
+    let steady = average > 0.0 && gaps.iter().all(|gap| (gap - average).abs() <= SPACING_TOLERANCE_DAYS);
+    steady.then(|| round_tenth(average))
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(&pp_date(value)?, "%Y%m%d").ok()
+}
+
+// Brisnet appends "g" to the effort code (B, H, D, E) of a work from the gate: "Bg", "Hg". A lone
+// "g" is the marker without an effort code.
+fn is_gate_work(description: &str) -> bool {
+    description.split_whitespace().any(|token| {
+        matches!(token.to_ascii_uppercase().as_str(), "G" | "BG" | "HG" | "DG" | "EG")
+    })
+}
+
+fn descending(a: Option<f64>, b: Option<f64>) -> std::cmp::Ordering {
+    b.unwrap_or(-1.0).total_cmp(&a.unwrap_or(-1.0))
+}
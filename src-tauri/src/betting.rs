@@ -0,0 +1,463 @@
+use std::collections::HashMap;
+use railbreaker_lib::models::racecard::{Race, Racecard};
+use serde::{Deserialize, Serialize};
+use crate::analysis::ranked_horses;
+use crate::analysis::model_parameters::{rank_race_with_parameters, ModelParameters};
+
+pub const ALL_TOKEN: &str = "ALL";
+// "TOP3" selects the model's top three scored runners in that race.
+pub const TOP_TOKEN: &str = "TOP";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BetType {
+    Exacta,
+    Trifecta,
+    Superfecta,
+    DailyDouble,
+    Pick3,
+    Pick4,
+    Pick5,
+    Pick6,
+}
+
+impl BetType {
+    // Finishing positions for a single-race bet, races for a multi-race bet.
+    pub fn legs(self) -> usize {
+        match self {
+            BetType::Exacta | BetType::DailyDouble => 2,
+            BetType::Trifecta | BetType::Pick3 => 3,
+            BetType::Superfecta | BetType::Pick4 => 4,
+            BetType::Pick5 => 5,
+            BetType::Pick6 => 6,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BetType::Exacta => "Exacta",
+            BetType::Trifecta => "Trifecta",
+            BetType::Superfecta => "Superfecta",
+            BetType::DailyDouble => "Daily Double",
+            BetType::Pick3 => "Pick 3",
+            BetType::Pick4 => "Pick 4",
+            BetType::Pick5 => "Pick 5",
+            BetType::Pick6 => "Pick 6",
+        }
+    }
+
+    pub fn is_multi_race(self) -> bool {
+        !matches!(self, BetType::Exacta | BetType::Trifecta | BetType::Superfecta)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TicketStructure {
+    // One selection list per position or leg; a straight ticket is a part-wheel of singles.
+    PartWheel,
+    // A single list that can finish in any order.
+    Box,
+    // The first list on top, the second list for every remaining position.
+    KeyWith,
+}
+
+// Each selection is a program number, ALL or TOPn.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TicketRequest {
+    pub bet_type: BetType,
+    // The race of a single-race bet, or the first leg of a multi-race bet.
+    pub race_number: i64,
+    pub base_amount: f64,
+    pub structure: TicketStructure,
+    pub selections: Vec<Vec<String>>,
+    #[serde(default)]
+    pub note: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BetTicket {
+    pub id: Option<i64>,
+    pub racecard_id: i64,
+    pub bet_type: BetType,
+    pub race_number: i64,
+    pub base_amount: f64,
+    pub structure: TicketStructure,
+    // As entered, so a ticket can be edited again.
+    pub selections: Vec<Vec<String>>,
+    // Program numbers per position or leg after ALL and TOPn are resolved.
+    pub legs: Vec<Vec<String>>,
+    pub combinations: i64,
+    pub total_cost: f64,
+    pub note: String,
+    pub created_at: Option<String>,
+}
+
+pub fn build_ticket(
+    racecard_id: i64,
+    racecard: &Racecard,
+    request: &TicketRequest,
+    parameters: &ModelParameters,
+) -> Result<BetTicket, String> {
+    if !request.base_amount.is_finite() || request.base_amount <= 0.0 {
+        return Err("The base amount must be greater than zero".to_string());
+    }
+
+    let bet_type = request.bet_type;
+    let leg_count = bet_type.legs();
+    let races: Vec<&Race> = if bet_type.is_multi_race() {
+        (0..leg_count as i64)
+            .map(|offset| find_race(racecard, request.race_number + offset))
+            .collect::<Result<_, _>>()?
+    } else {
+        vec![find_race(racecard, request.race_number)?; leg_count]
+    };
+
+    let tokens = expand_structure(request, leg_count)?;
+
+    let mut rankings: HashMap<i64, Vec<String>> = HashMap::new();
+    let mut legs = Vec::with_capacity(leg_count);
+    for (idx, (race, tokens)) in races.iter().zip(&tokens).enumerate() {
+        let leg = resolve_leg(racecard, race, tokens, parameters, &mut rankings)?;
+        if leg.is_empty() {
+            return Err(format!("{} {} has no selections", leg_name(bet_type), idx + 1));
+        }
+        legs.push(leg);
+    }
+
+    let combinations = count_combinations(bet_type, &legs);
+    if combinations == 0 {
+        return Err("These selections make no valid combinations".to_string());
+    }
+
+    Ok(BetTicket {
+        id: None,
+        racecard_id,
+        bet_type,
+        race_number: request.race_number,
+        base_amount: request.base_amount,
+        structure: request.structure,
+        selections: request.selections.clone(),
+        legs,
+        combinations,
+        total_cost: round_cents(combinations as f64 * request.base_amount),
+        note: request.note.clone(),
+        created_at: None,
+    })
+}
+
+// Single-race bets can't repeat a runner across positions; multi-race legs are independent.
+pub fn count_combinations(bet_type: BetType, legs: &[Vec<String>]) -> i64 {
+    if bet_type.is_multi_race() {
+        return legs.iter().map(|leg| leg.len() as i64).product();
+    }
+
+    fn count(legs: &[Vec<String>], used: &mut Vec<String>) -> i64 {
+        let Some((leg, rest)) = legs.split_first() else {
+            return 1;
+        };
+        let mut total = 0;
+        for horse in leg {
+            if used.contains(horse) {
+                continue;
+            }
+            used.push(horse.clone());
+            total += count(rest, used);
+            used.pop();
+        }
+        total
+    }
+    count(legs, &mut Vec::new())
+}
+
+fn expand_structure(request: &TicketRequest, leg_count: usize) -> Result<Vec<Vec<String>>, String> {
+    let selections = &request.selections;
+    match request.structure {
+        TicketStructure::PartWheel => {
+            if selections.len() != leg_count {
+                return Err(format!(
+                    "A {} needs {} selection lists, got {}",
+                    request.bet_type.label(),
+                    leg_count,
+                    selections.len()
+                ));
+            }
+            Ok(selections.clone())
+        }
+        TicketStructure::Box | TicketStructure::KeyWith if request.bet_type.is_multi_race() => {
+            Err(format!("A {} can't be boxed or keyed; use a part-wheel", request.bet_type.label()))
+        }
+        TicketStructure::Box => match selections.as_slice() {
+            [horses] => Ok(vec![horses.clone(); leg_count]),
+            _ => Err("A box takes a single selection list".to_string()),
+        },
+        TicketStructure::KeyWith => match selections.as_slice() {
+            [keys, with] => {
+                let mut tokens = vec![keys.clone()];
+                tokens.extend(std::iter::repeat_n(with.clone(), leg_count - 1));
+                Ok(tokens)
+            }
+            _ => Err("A key/with ticket takes a key list and a with list".to_string()),
+        },
+    }
+}
+
+// Program numbers in the order chosen, without duplicates or scratched runners.
+fn resolve_leg(
+    racecard: &Racecard,
+    race: &Race,
+    tokens: &[String],
+    parameters: &ModelParameters,
+    rankings: &mut HashMap<i64, Vec<String>>,
+) -> Result<Vec<String>, String> {
+    let race_number = race.race_number.map(|n| n as i64).unwrap_or_default();
+    let entries: Vec<(String, bool)> = race
+        .horses
+        .iter()
+        .map(|h| (h.program_number.trim().to_string(), h.scratched))
+        .collect();
+
+    resolve_tokens(race_number, &entries, tokens, || {
+        rankings
+            .entry(race_number)
+            .or_insert_with(|| ranked_starters(racecard, race, parameters))
+            .clone()
+    })
+}
+
+// The selection rules over (program number, scratched) entries; rank is only called for TOPn.
+fn resolve_tokens(
+    race_number: i64,
+    entries: &[(String, bool)],
+    tokens: &[String],
+    mut rank: impl FnMut() -> Vec<String>,
+) -> Result<Vec<String>, String> {
+    let mut ranked: Option<Vec<String>> = None;
+    let mut leg: Vec<String> = Vec::new();
+
+    for token in tokens {
+        let token = token.trim().to_uppercase();
+        if token.is_empty() {
+            continue;
+        }
+
+        if token == ALL_TOKEN {
+            for (horse, _) in entries.iter().filter(|(_, scratched)| !scratched) {
+                push(&mut leg, horse);
+            }
+        } else if let Some(count) = token.strip_prefix(TOP_TOKEN) {
+            let count: usize = count
+                .parse()
+                .map_err(|_| format!("Invalid selection {} in race {}", token, race_number))?;
+            let ranked = ranked.get_or_insert_with(&mut rank);
+            for horse in ranked.iter().take(count) {
+                push(&mut leg, horse);
+            }
+        } else {
+            match entries.iter().find(|(number, _)| number.eq_ignore_ascii_case(&token)) {
+                Some((_, true)) => {
+                    return Err(format!("#{} is scratched in race {}", token, race_number));
+                }
+                Some((number, false)) => push(&mut leg, number),
+                None => return Err(format!("There is no #{} in race {}", token, race_number)),
+            }
+        }
+    }
+
+    Ok(leg)
+}
+
+// Starters the model scored, best first.
+fn ranked_starters(racecard: &Racecard, race: &Race, parameters: &ModelParameters) -> Vec<String> {
+    let meta = rank_race_with_parameters(race, Some(racecard.date.as_str()), parameters);
+    let entries: Vec<(String, bool)> = race
+        .horses
+        .iter()
+        .map(|h| (h.program_number.trim().to_string(), h.scratched))
+        .collect();
+    unscratched(
+        ranked_horses(&meta).into_iter().map(|h| h.program_number.trim().to_string()),
+        &entries,
+    )
+}
+
+// The model may still rank a horse scratched after the card was loaded.
+fn unscratched(ranked: impl IntoIterator<Item = String>, entries: &[(String, bool)]) -> Vec<String> {
+    ranked
+        .into_iter()
+        .filter(|number| entries.iter().any(|(n, scratched)| !scratched && n == number))
+        .collect()
+}
+
+fn push(leg: &mut Vec<String>, horse: &str) {
+    if !leg.iter().any(|h| h == horse) {
+        leg.push(horse.to_string());
+    }
+}
+
+fn find_race(racecard: &Racecard, race_number: i64) -> Result<&Race, String> {
+    racecard
+        .races
+        .iter()
+        .find(|race| race.race_number.map(|n| n as i64) == Some(race_number))
+        .ok_or_else(|| format!("There is no race {} on this racecard", race_number))
+}
+
+fn leg_name(bet_type: BetType) -> &'static str {
+    if bet_type.is_multi_race() { "Leg" } else { "Position" }
+}
+
+fn round_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+// "1,4 / 2,3,5,7 / 2" for listings and printouts.
+pub fn ticket_legs_text(ticket: &BetTicket) -> String {
+    ticket
+        .legs
+        .iter()
+        .map(|leg| leg.join(","))
+        .collect::<Vec<_>>()
+        .join(" / ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn horses(numbers: &[&str]) -> Vec<String> {
+        numbers.iter().map(|n| n.to_string()).collect()
+    }
+
+    fn request(bet_type: BetType, structure: TicketStructure, selections: &[&[&str]]) -> TicketRequest {
+        TicketRequest {
+            bet_type,
+            race_number: 1,
+            base_amount: 1.0,
+            structure,
+            selections: selections.iter().map(|s| horses(s)).collect(),
+            note: String::new(),
+        }
+    }
+
+    fn combinations(request: &TicketRequest) -> i64 {
+        let legs = expand_structure(request, request.bet_type.legs()).unwrap();
+        count_combinations(request.bet_type, &legs)
+    }
+
+    #[test]
+    fn box_counts_every_order() {
+        let exacta = request(BetType::Exacta, TicketStructure::Box, &[&["1", "2", "3"]]);
+        assert_eq!(combinations(&exacta), 6);
+
+        let trifecta = request(BetType::Trifecta, TicketStructure::Box, &[&["1", "2", "3", "4"]]);
+        assert_eq!(combinations(&trifecta), 24);
+
+        let superfecta = request(BetType::Superfecta, TicketStructure::Box, &[&["1", "2", "3"]]);
+        assert_eq!(combinations(&superfecta), 0);
+    }
+
+    #[test]
+    fn key_runs_over_the_with_list() {
+        let trifecta = request(BetType::Trifecta, TicketStructure::KeyWith, &[&["1"], &["2", "3", "4"]]);
+        assert_eq!(
+            expand_structure(&trifecta, 3).unwrap(),
+            vec![horses(&["1"]), horses(&["2", "3", "4"]), horses(&["2", "3", "4"])]
+        );
+        assert_eq!(combinations(&trifecta), 6);
+
+        // A key horse also in the with list can't be used twice in one combination.
+        let exacta = request(BetType::Exacta, TicketStructure::KeyWith, &[&["1", "2"], &["1", "2", "3"]]);
+        assert_eq!(combinations(&exacta), 4);
+    }
+
+    #[test]
+    fn wheel_runs_over_the_field() {
+        let exacta = request(BetType::Exacta, TicketStructure::PartWheel, &[&["5"], &["1", "2", "3", "4", "5"]]);
+        assert_eq!(combinations(&exacta), 4);
+    }
+
+    #[test]
+    fn part_wheel_skips_repeated_horses_in_one_race() {
+        let trifecta = request(BetType::Trifecta, TicketStructure::PartWheel, &[&["1", "2"], &["1", "2"], &["1", "2", "3"]]);
+        assert_eq!(combinations(&trifecta), 2);
+
+        let superfecta = request(
+            BetType::Superfecta,
+            TicketStructure::PartWheel,
+            &[&["1"], &["1", "2"], &["2", "3"], &["3", "4", "5"]],
+        );
+        assert_eq!(combinations(&superfecta), 2);
+    }
+
+    #[test]
+    fn multi_race_legs_are_independent() {
+        let pick3 = request(BetType::Pick3, TicketStructure::PartWheel, &[&["1", "2"], &["1", "2"], &["1"]]);
+        assert_eq!(combinations(&pick3), 4);
+
+        let double = request(BetType::DailyDouble, TicketStructure::PartWheel, &[&["3"], &["3"]]);
+        assert_eq!(combinations(&double), 1);
+    }
+
+    #[test]
+    fn structure_errors() {
+        let short = request(BetType::Trifecta, TicketStructure::PartWheel, &[&["1"], &["2"]]);
+        assert!(expand_structure(&short, 3).is_err());
+
+        let boxed_pick = request(BetType::Pick4, TicketStructure::Box, &[&["1", "2"]]);
+        assert!(expand_structure(&boxed_pick, 4).is_err());
+
+        let keyed_pick = request(BetType::Pick3, TicketStructure::KeyWith, &[&["1"], &["2"]]);
+        assert!(expand_structure(&keyed_pick, 3).is_err());
+
+        let two_boxes = request(BetType::Exacta, TicketStructure::Box, &[&["1"], &["2"]]);
+        assert!(expand_structure(&two_boxes, 2).is_err());
+
+        let key_only = request(BetType::Exacta, TicketStructure::KeyWith, &[&["1"]]);
+        assert!(expand_structure(&key_only, 2).is_err());
+    }
+
+    fn entries(horses: &[(&str, bool)]) -> Vec<(String, bool)> {
+        horses.iter().map(|(n, scratched)| (n.to_string(), *scratched)).collect()
+    }
+
+    #[test]
+    fn all_and_top_skip_scratched_horses() {
+        let field = entries(&[("1", false), ("2", false), ("3", true), ("4", false), ("5", false)]);
+        // The model's order still lists the scratched #3 and leaves #4 unscored.
+        let model = || unscratched(horses(&["3", "5", "1", "2"]), &field);
+
+        assert_eq!(resolve_tokens(1, &field, &horses(&["ALL"]), model).unwrap(), horses(&["1", "2", "4", "5"]));
+        assert_eq!(resolve_tokens(1, &field, &horses(&["top2"]), model).unwrap(), horses(&["5", "1"]));
+        // Asking for more than were scored gives every scored starter.
+        assert_eq!(resolve_tokens(1, &field, &horses(&["TOP9"]), model).unwrap(), horses(&["5", "1", "2"]));
+        assert_eq!(
+            resolve_tokens(1, &field, &horses(&["TOP1", "4", "ALL"]), model).unwrap(),
+            horses(&["5", "4", "1", "2"])
+        );
+    }
+
+    #[test]
+    fn model_is_ranked_once_per_leg() {
+        let field = entries(&[("1", false), ("2", false)]);
+        let mut calls = 0;
+        let leg = resolve_tokens(1, &field, &horses(&["TOP1", "TOP2"]), || {
+            calls += 1;
+            horses(&["2", "1"])
+        })
+        .unwrap();
+        assert_eq!(leg, horses(&["2", "1"]));
+        assert_eq!(calls, 1);
+
+        // ALL and program numbers never need the model.
+        resolve_tokens(1, &field, &horses(&["ALL", "1"]), || panic!("ranked")).unwrap();
+    }
+
+    #[test]
+    fn bad_selections_are_errors() {
+        let field = entries(&[("1", false), ("1A", false), ("2", true)]);
+        let model = || horses(&["1"]);
+        assert_eq!(resolve_tokens(3, &field, &horses(&["1a"]), model).unwrap(), horses(&["1A"]));
+        assert_eq!(resolve_tokens(3, &field, &horses(&["2"]), model).unwrap_err(), "#2 is scratched in race 3");
+        assert_eq!(resolve_tokens(3, &field, &horses(&["7"]), model).unwrap_err(), "There is no #7 in race 3");
+        assert!(resolve_tokens(3, &field, &horses(&["TOPX"]), model).is_err());
+    }
+}
@@ -0,0 +1,622 @@
+use std::path::PathBuf;
+use railbreaker_lib::contextual_speed_and_pace_model::RaceMeta;
+use sqlx::{Row, SqlitePool};
+use crate::analysis::{enum_label, ranked_horses};
+use crate::analysis::class::{race_class, RaceClass};
+use crate::analysis::fair_odds::{fair_odds_line, FairOddsLine};
+use crate::analysis::model_parameters::rank_race_with_parameters;
+use crate::analysis::trip_model::{rank_race_trips, TripRanking};
+use crate::commands::process_zip_file_commands::ExtractStatus;
+use crate::export::{export_racecard, ExportFormat, ExportOptions, FieldSet};
+use crate::betting::ticket_legs_text;
+use crate::pdf::{write_bet_tickets_pdf, write_racecard_pdf};
+use crate::sqlite::bet_tickets::read_bet_tickets;
+use crate::sqlite::live_odds::current_odds;
+use crate::import::{import_racecard_files, ImportedFile};
+use crate::sqlite::racecards::read_racecard_by_id;
+use crate::states::global_state::global_state;
+
+const USAGE: &str = "Usage: railbreaker-cli [--dir <working directory>] <command> [args]
+
+Commands:
+  import <zip file or directory>...   Import Brisnet zips and .DRF files into the database
+  list                                List stored racecards
+  dump <racecard id> [--out <file>]   Write a stored racecard as JSON
+  rank <racecard id> [--race <n>] [--json]
+                                      Run the Contextual Speed and Pace Model
+  trips <racecard id> [--race <n>] [--json]
+                                      Run the trip handicapping model
+  odds <racecard id> [--race <n>] [--json]
+                                      Fair-odds line against the morning line and entered odds
+  export <racecard id> --out <file> [--format csv|json] [--fields summary|handicapping|full] [--race <n>]...
+                                      Export a racecard with the model output
+  pdf <racecard id> --out <file> [--race <n>]...
+                                      Render a racecard printout to PDF
+  tickets <racecard id> [--out <file>]
+                                      List saved bet tickets, or print them to PDF
+  backtest [--json]                   Backtest the model against stored results
+  connections [--trainer <name>] [--jockey <name>] [--track <code>]
+                                      Trainer, jockey or combo statistics as JSON";
+
+pub async fn run(args: Vec<String>) -> Result<(), String> {
+    let mut args = args.into_iter().peekable();
+
+    let mut working_directory = None;
+    if args.peek().map(|a| a == "--dir").unwrap_or(false) {
+        args.next();
+        working_directory = Some(PathBuf::from(args.next().ok_or("--dir requires a path")?));
+    }
+
+    let command = match args.next() {
+        Some(command) => command,
+        None => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+    };
+    let args: Vec<String> = args.collect();
+
+    if command == "-h" || command == "--help" || command == "help" {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    let pool = open_database(working_directory).await?;
+
+    match command.as_str() {
+        "import" => import(&pool, args).await,
+        "list" => list(&pool).await,
+        "dump" => dump(&pool, args).await,
+        "rank" => rank(&pool, args).await,
+        "trips" => trips(&pool, args).await,
+        "odds" => odds(&pool, args).await,
+        "export" => export(&pool, args).await,
+        "pdf" => pdf(&pool, args).await,
+        "tickets" => tickets(&pool, args).await,
+        "backtest" => backtest(&pool, args).await,
+        "connections" => connections(&pool, args).await,
+        other => Err(format!("Unknown command: {}\n\n{}", other, USAGE)),
+    }
+}
+
+async fn open_database(working_directory: Option<PathBuf>) -> Result<SqlitePool, String> {
+    let current_directory = match working_directory {
+        Some(dir) => dir,
+        None => std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?,
+    };
+
+    let racecard_path = current_directory.join("Racecards");
+    std::fs::create_dir_all(&racecard_path)
+        .map_err(|e| format!("Failed to create Racecards directory: {}", e))?;
+
+    {
+        let mut gs = global_state()
+            .lock()
+            .map_err(|e| format!("Failed to lock global state: {}", e))?;
+        gs.current_directory = current_directory.to_string_lossy().to_string();
+        gs.racecards_directory = racecard_path.to_string_lossy().to_string();
+    }
+
+    crate::tracks::init_tracks(&current_directory)?;
+
+    let database_url = crate::sqlite::get_database_file_path()?;
+    crate::sqlite::init_database(&database_url)
+        .await
+        .map_err(|e| format!("Failed to initialize database: {}", e))
+}
+
+async fn import(pool: &SqlitePool, args: Vec<String>) -> Result<(), String> {
+    if args.is_empty() {
+        return Err("import requires at least one zip file or directory".to_string());
+    }
+
+    let (mut imported, mut skipped, mut failed) = (0, 0, 0);
+    for path in args {
+        let files = match import_racecard_files(pool, &path).await {
+            Ok(files) => files,
+            Err(e) => {
+                failed += 1;
+                eprintln!("failed    {}  {}", path, e);
+                continue;
+            }
+        };
+
+        for ImportedFile { file, racecard, watched } in files {
+            match (file.status, racecard) {
+                (ExtractStatus::Extracted, Some(racecard)) => {
+                    imported += 1;
+                    println!(
+                        "imported  {}  #{} {} {} ({} races)",
+                        file.zip_file_name,
+                        racecard.id,
+                        racecard.track,
+                        racecard.date,
+                        racecard.races.len()
+                    );
+                    for horse in watched {
+                        println!(
+                            "          watched: race {} #{} {}",
+                            horse.race_number.map(|n| n.to_string()).unwrap_or_else(|| "-".to_string()),
+                            horse.program_number,
+                            horse.horse_name
+                        );
+                    }
+                }
+                (ExtractStatus::Skipped, _) => {
+                    skipped += 1;
+                    println!("skipped   {}  {}", file.zip_file_name, file.message);
+                }
+                _ => {
+                    failed += 1;
+                    eprintln!("failed    {}  {}", file.zip_file_name, file.message);
+                }
+            }
+        }
+    }
+
+    println!("{} imported, {} skipped, {} failed", imported, skipped, failed);
+
+    if failed > 0 {
+        return Err(format!("{} file(s) failed to import", failed));
+    }
+
+    Ok(())
+}
+
+async fn list(pool: &SqlitePool) -> Result<(), String> {
+    let rows = sqlx::query(
+        "SELECT rc.id, rc.date, rc.track_code, rc.track, rc.zip_file_name,
+            (SELECT COUNT(*) FROM races r WHERE r.racecard_id = rc.id) AS race_count
+         FROM racecards rc
+         ORDER BY rc.date DESC, rc.track ASC;",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load racecards: {}", e))?;
+
+    println!("{:>5}  {:<10}  {:<5}  {:<28}  {:>5}  Zip", "ID", "Date", "Code", "Track", "Races");
+    for row in rows {
+        println!(
+            "{:>5}  {:<10}  {:<5}  {:<28}  {:>5}  {}",
+            row.get::<i64, _>("id"),
+            row.get::<String, _>("date"),
+            row.get::<String, _>("track_code"),
+            row.get::<String, _>("track"),
+            row.get::<i64, _>("race_count"),
+            row.get::<String, _>("zip_file_name"),
+        );
+    }
+
+    Ok(())
+}
+
+async fn dump(pool: &SqlitePool, args: Vec<String>) -> Result<(), String> {
+    let mut args = args.into_iter();
+    let racecard_id = parse_racecard_id(args.next())?;
+
+    let mut out_path = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out" => out_path = Some(args.next().ok_or("--out requires a path")?),
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+    }
+
+    let racecard = read_racecard_by_id(pool, racecard_id)
+        .await
+        .map_err(|e| format!("Failed to load racecard: {}", e))?;
+    let mut racecard = serde_json::to_value(&racecard)
+        .map_err(|e| format!("Failed to serialize racecard: {}", e))?;
+    crate::tracks::resolve_track_names(&mut racecard);
+
+    match out_path {
+        Some(path) => crate::files::write_json_file(&path, &racecard).await,
+        None => {
+            let json = serde_json::to_string_pretty(&racecard)
+                .map_err(|e| format!("Failed to serialize racecard: {}", e))?;
+            println!("{}", json);
+            Ok(())
+        }
+    }
+}
+
+async fn rank(pool: &SqlitePool, args: Vec<String>) -> Result<(), String> {
+    let mut args = args.into_iter();
+    let racecard_id = parse_racecard_id(args.next())?;
+
+    let mut race_number = None;
+    let mut as_json = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--race" => race_number = Some(parse_race_number(args.next())?),
+            "--json" => as_json = true,
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+    }
+
+    let racecard = read_racecard_by_id(pool, racecard_id)
+        .await
+        .map_err(|e| format!("Failed to load racecard: {}", e))?;
+
+    let parameters = crate::commands::config_file_commands::load_model_parameters().await;
+    let races: Vec<_> = racecard
+        .races
+        .iter()
+        .filter(|race| race_number.is_none() || race.race_number.map(|n| n as i64) == race_number)
+        .collect();
+    let metas: Vec<RaceMeta> = races
+        .iter()
+        .map(|race| rank_race_with_parameters(race, Some(racecard.date.as_str()), &parameters))
+        .collect();
+
+    if metas.is_empty() {
+        return Err("No matching races on this racecard".to_string());
+    }
+
+    if as_json {
+        let json = serde_json::to_string_pretty(&metas)
+            .map_err(|e| format!("Failed to serialize race analysis: {}", e))?;
+        println!("{}", json);
+        return Ok(());
+    }
+
+    println!("{} {}", racecard.track, racecard.long_date);
+    for (meta, race) in metas.iter().zip(&races) {
+        print_race_meta(meta, &race_class(race));
+    }
+
+    Ok(())
+}
+
+async fn trips(pool: &SqlitePool, args: Vec<String>) -> Result<(), String> {
+    let mut args = args.into_iter();
+    let racecard_id = parse_racecard_id(args.next())?;
+
+    let mut race_number = None;
+    let mut as_json = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--race" => race_number = Some(parse_race_number(args.next())?),
+            "--json" => as_json = true,
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+    }
+
+    let racecard = read_racecard_by_id(pool, racecard_id)
+        .await
+        .map_err(|e| format!("Failed to load racecard: {}", e))?;
+    let weights = crate::commands::config_file_commands::load_trip_weights().await;
+
+    let rankings: Vec<TripRanking> = racecard
+        .races
+        .iter()
+        .filter(|race| race_number.is_none() || race.race_number.map(|n| n as i64) == race_number)
+        .map(|race| rank_race_trips(race, Some(racecard.date.as_str()), &weights))
+        .collect();
+
+    if rankings.is_empty() {
+        return Err("No matching races on this racecard".to_string());
+    }
+
+    if as_json {
+        let json = serde_json::to_string_pretty(&rankings)
+            .map_err(|e| format!("Failed to serialize trip analysis: {}", e))?;
+        println!("{}", json);
+        return Ok(());
+    }
+
+    println!("{} {}", racecard.track, racecard.long_date);
+    for ranking in &rankings {
+        println!(
+            "\nRace {}",
+            ranking.race_number.map(|n| n.to_string()).unwrap_or_else(|| "-".to_string())
+        );
+        for horse in &ranking.horses {
+            println!(
+                "  {:>4}  {:<24} {:>6.1}{}",
+                horse.program_number,
+                horse.horse_name,
+                horse.score,
+                if horse.scratched { "  scratched" } else { "" }
+            );
+            for trip in &horse.trips {
+                println!(
+                    "        {:>4}  {:<9} {}",
+                    trip.days_back.map(|d| d.to_string()).unwrap_or_default(),
+                    enum_label(&trip.outlook),
+                    trip.comment
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn odds(pool: &SqlitePool, args: Vec<String>) -> Result<(), String> {
+    let mut args = args.into_iter();
+    let racecard_id = parse_racecard_id(args.next())?;
+
+    let mut race_number = None;
+    let mut as_json = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--race" => race_number = Some(parse_race_number(args.next())?),
+            "--json" => as_json = true,
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+    }
+
+    let racecard = read_racecard_by_id(pool, racecard_id)
+        .await
+        .map_err(|e| format!("Failed to load racecard: {}", e))?;
+    let parameters = crate::commands::config_file_commands::load_model_parameters().await;
+    let settings = crate::commands::config_file_commands::load_fair_odds_settings().await;
+
+    let mut lines: Vec<FairOddsLine> = Vec::new();
+    for race in &racecard.races {
+        if race_number.is_some() && race.race_number.map(|n| n as i64) != race_number {
+            continue;
+        }
+        let meta = rank_race_with_parameters(race, Some(racecard.date.as_str()), &parameters);
+        let live_odds = current_odds(pool, race.id).await?;
+        lines.push(fair_odds_line(race, &meta, &settings, &live_odds));
+    }
+
+    if lines.is_empty() {
+        return Err("No matching races on this racecard".to_string());
+    }
+
+    if as_json {
+        let json = serde_json::to_string_pretty(&lines)
+            .map_err(|e| format!("Failed to serialize fair odds: {}", e))?;
+        println!("{}", json);
+        return Ok(());
+    }
+
+    println!("{} {}  (takeout {:.0}%)", racecard.track, racecard.long_date, settings.takeout * 100.0);
+    let odds = |odds: Option<f64>| odds.map(|o| format!("{:.1}", o)).unwrap_or_else(|| "-".to_string());
+    for line in &lines {
+        println!(
+            "\nRace {}",
+            line.race_number.map(|n| n.to_string()).unwrap_or_else(|| "-".to_string())
+        );
+        for horse in &line.horses {
+            println!(
+                "  {:>4}  {:<24} {:>6}  fair {:>6}  ML {:>6} {:<8}  live {:>6} {}",
+                horse.program_number,
+                horse.horse_name,
+                horse.probability.map(|p| format!("{:.1}%", p * 100.0)).unwrap_or_else(|| "-".to_string()),
+                odds(horse.fair_odds),
+                odds(horse.morning_line_odds),
+                horse.morning_line_value.map(|v| enum_label(&v)).unwrap_or_default(),
+                odds(horse.live_odds),
+                horse.live_value.map(|v| enum_label(&v)).unwrap_or_default()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn print_race_meta(meta: &RaceMeta, class: &RaceClass) {
+    println!(
+        "\nRace {}  Shape: {}  EPI: {:.2}  Confidence: {}  Class: {}",
+        meta.race_number.map(|n| n.to_string()).unwrap_or_else(|| "-".to_string()),
+        enum_label(&meta.shape),
+        meta.epi,
+        enum_label(&meta.confidence),
+        class.level.map(|l| format!("{:.1}", l)).unwrap_or_else(|| "-".to_string())
+    );
+
+    if let Some(win_bet) = &meta.win_bet {
+        println!(
+            "  Win bet: #{} {} (min odds {})  {}",
+            win_bet.program_number,
+            win_bet.horse_name,
+            win_bet
+                .min_odds
+                .map(|o| format!("{:.1}", o))
+                .unwrap_or_else(|| "-".to_string()),
+            win_bet.reason
+        );
+    }
+
+    if let Some(rank_result) = &meta.race_rank_result {
+        let unscored = rank_result.horses.iter().filter(|h| h.score.is_none());
+
+        for horse in ranked_horses(meta).into_iter().chain(unscored) {
+            let horse_class = class
+                .horses
+                .iter()
+                .find(|c| c.program_number == horse.program_number.trim());
+            println!(
+                "  {:>4}  {:<24} {:>4}  {:>7}  {:<5} {}",
+                horse.program_number,
+                horse.horse_name,
+                enum_label(&horse.run_style),
+                horse
+                    .score
+                    .map(|s| format!("{:.1}", s))
+                    .unwrap_or_else(|| "-".to_string()),
+                horse_class.and_then(|c| c.class_move).map(|m| enum_label(&m)).unwrap_or_default(),
+                horse_class.and_then(|c| c.claim_move).map(|m| enum_label(&m)).unwrap_or_default()
+            );
+        }
+    }
+}
+
+async fn export(pool: &SqlitePool, args: Vec<String>) -> Result<(), String> {
+    let mut args = args.into_iter();
+    let racecard_id = parse_racecard_id(args.next())?;
+
+    let mut out_path = None;
+    let mut format = None;
+    let mut field_set = FieldSet::default();
+    let mut race_numbers = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out" => out_path = Some(args.next().ok_or("--out requires a path")?),
+            "--format" => {
+                format = Some(match args.next().as_deref() {
+                    Some("csv") => ExportFormat::Csv,
+                    Some("json") => ExportFormat::Json,
+                    _ => return Err("--format must be csv or json".to_string()),
+                });
+            }
+            "--fields" => {
+                field_set = match args.next().as_deref() {
+                    Some("summary") => FieldSet::Summary,
+                    Some("handicapping") => FieldSet::Handicapping,
+                    Some("full") => FieldSet::Full,
+                    _ => return Err("--fields must be summary, handicapping or full".to_string()),
+                };
+            }
+            "--race" => race_numbers.push(parse_race_number(args.next())?),
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+    }
+
+    let out_path = out_path.ok_or("export requires --out <file>")?;
+    // Without --format the extension decides, defaulting to CSV.
+    let format = format.unwrap_or(if out_path.to_lowercase().ends_with(".json") {
+        ExportFormat::Json
+    } else {
+        ExportFormat::Csv
+    });
+
+    let racecard = read_racecard_by_id(pool, racecard_id)
+        .await
+        .map_err(|e| format!("Failed to load racecard: {}", e))?;
+
+    let options = ExportOptions {
+        format,
+        field_set,
+        race_numbers: if race_numbers.is_empty() { None } else { Some(race_numbers) },
+    };
+    let report = export_racecard(pool, &racecard, &options, &out_path).await?;
+    println!("Exported {} races, {} horses to {}", report.races, report.horses, report.path);
+
+    Ok(())
+}
+
+async fn pdf(pool: &SqlitePool, args: Vec<String>) -> Result<(), String> {
+    let mut args = args.into_iter();
+    let racecard_id = parse_racecard_id(args.next())?;
+
+    let mut out_path = None;
+    let mut race_numbers = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out" => out_path = Some(args.next().ok_or("--out requires a path")?),
+            "--race" => race_numbers.push(parse_race_number(args.next())?),
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+    }
+
+    let out_path = out_path.ok_or("pdf requires --out <file>")?;
+    let racecard = read_racecard_by_id(pool, racecard_id)
+        .await
+        .map_err(|e| format!("Failed to load racecard: {}", e))?;
+
+    let race_numbers = if race_numbers.is_empty() { None } else { Some(race_numbers.as_slice()) };
+    let report = write_racecard_pdf(&racecard, race_numbers, &out_path).await?;
+    println!("Wrote {} pages to {}", report.pages, report.path);
+
+    Ok(())
+}
+
+async fn tickets(pool: &SqlitePool, args: Vec<String>) -> Result<(), String> {
+    let mut args = args.into_iter();
+    let racecard_id = parse_racecard_id(args.next())?;
+
+    let mut out_path = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out" => out_path = Some(args.next().ok_or("--out requires a path")?),
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+    }
+
+    let racecard = read_racecard_by_id(pool, racecard_id)
+        .await
+        .map_err(|e| format!("Failed to load racecard: {}", e))?;
+    let tickets = read_bet_tickets(pool, racecard_id).await?;
+
+    if let Some(out_path) = out_path {
+        let report = write_bet_tickets_pdf(&racecard, &tickets, &out_path).await?;
+        println!("Wrote {} pages to {}", report.pages, report.path);
+        return Ok(());
+    }
+
+    println!("{} {}", racecard.track, racecard.long_date);
+    for ticket in &tickets {
+        println!(
+            "  #{:<4} Race {:>2}  {:<12} {:>6.2}  {:>5} x  {:>8.2}  {}",
+            ticket.id.unwrap_or_default(),
+            ticket.race_number,
+            ticket.bet_type.label(),
+            ticket.base_amount,
+            ticket.combinations,
+            ticket.total_cost,
+            ticket_legs_text(ticket)
+        );
+    }
+    let total: f64 = tickets.iter().map(|t| t.total_cost).sum();
+    println!("{} tickets  Total {:.2}", tickets.len(), total);
+
+    Ok(())
+}
+
+async fn backtest(pool: &SqlitePool, args: Vec<String>) -> Result<(), String> {
+    let as_json = match args.first().map(|a| a.as_str()) {
+        None => false,
+        Some("--json") => true,
+        Some(other) => return Err(format!("Unknown argument: {}", other)),
+    };
+
+    let report = crate::analysis::backtest::backtest_database(pool).await?;
+
+    if as_json {
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| format!("Failed to serialize backtest report: {}", e))?;
+        println!("{}", json);
+    } else {
+        print!("{}", crate::analysis::backtest::format_report(&report));
+    }
+
+    Ok(())
+}
+
+async fn connections(pool: &SqlitePool, args: Vec<String>) -> Result<(), String> {
+    let mut query = crate::analysis::connections::ConnectionQuery::default();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--trainer" => query.trainer = Some(args.next().ok_or("--trainer requires a name")?),
+            "--jockey" => query.jockey = Some(args.next().ok_or("--jockey requires a name")?),
+            "--track" => query.track_code = Some(args.next().ok_or("--track requires a track code")?),
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+    }
+
+    let report = crate::analysis::connections::connection_stats(pool, &query).await?;
+    let json = serde_json::to_string_pretty(&report)
+        .map_err(|e| format!("Failed to serialize connection statistics: {}", e))?;
+    println!("{}", json);
+
+    Ok(())
+}
+
+fn parse_racecard_id(arg: Option<String>) -> Result<i64, String> {
+    let arg = arg.ok_or("A racecard id is required (see `railbreaker-cli list`)")?;
+    arg.parse::<i64>()
+        .map_err(|_| format!("Invalid racecard id: {}", arg))
+}
+
+fn parse_race_number(arg: Option<String>) -> Result<i64, String> {
+    let arg = arg.ok_or("--race requires a race number")?;
+    arg.parse::<i64>()
+        .map_err(|_| format!("Invalid race number: {}", arg))
+}
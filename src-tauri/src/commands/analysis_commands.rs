@@ -1,14 +1,122 @@
-use railbreaker_lib::contextual_speed_and_pace_model::{RaceMeta, derive_race_meta};
+use railbreaker_lib::contextual_speed_and_pace_model::RaceMeta;
 use railbreaker_lib::models::racecard::Race;
+use std::collections::HashMap;
 use serde_json::{Value};
+use sqlx::SqlitePool;
+use tauri::State;
+use crate::analysis::backtest::{backtest_database, BacktestReport};
+use crate::analysis::class::{race_class, RaceClass};
+use crate::analysis::bias::{racecard_bias, track_bias, BiasQuery, BiasReport, RaceBias};
+use crate::analysis::fair_odds::{fair_odds_line, FairOddsLine};
+use crate::analysis::form_cycle::{race_form_cycles, RaceForm};
+use crate::analysis::figures::{race_figures, RaceFigures};
+use crate::analysis::connections::{connection_stats, ConnectionQuery, ConnectionReport};
+use crate::analysis::model_parameters::rank_race_with_parameters;
+use crate::analysis::trip_model::{rank_race_trips, TripRanking};
+use crate::analysis::workouts::{race_workouts, RaceWorkouts, WorkoutQuery};
+use crate::sqlite::live_odds::current_odds;
+use crate::commands::config_file_commands::{
+    load_fair_odds_settings, load_figure_settings, load_model_parameters, load_trip_weights,
+};
 
 #[tauri::command]
-pub fn rank_race(
+pub async fn rank_race(
     race: Value,
     racecard_date: Option<String>,
 ) -> Result<RaceMeta, String> {
     let race: Race =
         serde_json::from_value(race).map_err(|e| format!("Failed to parse race payload: {}", e))?;
     let date = racecard_date.as_deref();
-    Ok(derive_race_meta(&race, date))
+    let parameters = load_model_parameters().await;
+    Ok(rank_race_with_parameters(&race, date, &parameters))
+}
+
+#[tauri::command]
+pub async fn rank_trips(
+    race: Value,
+    racecard_date: Option<String>,
+) -> Result<TripRanking, String> {
+    let race: Race =
+        serde_json::from_value(race).map_err(|e| format!("Failed to parse race payload: {}", e))?;
+    let weights = load_trip_weights().await;
+    Ok(rank_race_trips(&race, racecard_date.as_deref(), &weights))
+}
+
+// `live_odds` maps program numbers to odds-to-1; without it the latest stored odds are used.
+#[tauri::command]
+pub async fn get_fair_odds(
+    pool: State<'_, SqlitePool>,
+    race: Value,
+    racecard_date: Option<String>,
+    live_odds: Option<HashMap<String, f64>>,
+) -> Result<FairOddsLine, String> {
+    let race: Race =
+        serde_json::from_value(race).map_err(|e| format!("Failed to parse race payload: {}", e))?;
+    let parameters = load_model_parameters().await;
+    let settings = load_fair_odds_settings().await;
+    let live_odds = match live_odds {
+        Some(live_odds) => live_odds,
+        None => current_odds(&pool, race.id).await?,
+    };
+    let meta = rank_race_with_parameters(&race, racecard_date.as_deref(), &parameters);
+    Ok(fair_odds_line(&race, &meta, &settings, &live_odds))
+}
+
+// Class levels for the race and each past race, with drops, rises and claim-tag moves per horse.
+#[tauri::command]
+pub async fn get_race_class(race: Value) -> Result<RaceClass, String> {
+    let race: Race =
+        serde_json::from_value(race).map_err(|e| format!("Failed to parse race payload: {}", e))?;
+    Ok(race_class(&race))
+}
+
+// Per-horse workout reports, filtered and sorted by `query`; every horse in program order without it.
+#[tauri::command]
+pub async fn get_workout_report(
+    race: Value,
+    racecard_date: Option<String>,
+    query: Option<WorkoutQuery>,
+) -> Result<RaceWorkouts, String> {
+    let race: Race =
+        serde_json::from_value(race).map_err(|e| format!("Failed to parse race payload: {}", e))?;
+    Ok(race_workouts(&race, racecard_date.as_deref(), &query.unwrap_or_default()))
+}
+
+#[tauri::command]
+pub async fn get_form_cycles(race: Value) -> Result<RaceForm, String> {
+    let race: Race =
+        serde_json::from_value(race).map_err(|e| format!("Failed to parse race payload: {}", e))?;
+    Ok(race_form_cycles(&race))
+}
+
+// Our speed and pace figures for every PP line in the race, next to Brisnet's.
+#[tauri::command]
+pub async fn get_figures(race: Value) -> Result<RaceFigures, String> {
+    let race: Race =
+        serde_json::from_value(race).map_err(|e| format!("Failed to parse race payload: {}", e))?;
+    let settings = load_figure_settings().await;
+    Ok(race_figures(&race, &settings))
+}
+
+#[tauri::command]
+pub async fn run_backtest(pool: State<'_, SqlitePool>) -> Result<BacktestReport, String> {
+    backtest_database(&pool).await
+}
+
+#[tauri::command]
+pub async fn get_track_bias(pool: State<'_, SqlitePool>, query: BiasQuery) -> Result<BiasReport, String> {
+    track_bias(&pool, &query).await
+}
+
+#[tauri::command]
+pub async fn get_racecard_bias(pool: State<'_, SqlitePool>, racecard_id: i64) -> Result<Vec<RaceBias>, String> {
+    racecard_bias(&pool, racecard_id).await
+}
+
+#[tauri::command]
+pub async fn get_connection_stats(
+    pool: State<'_, SqlitePool>,
+    query: ConnectionQuery,
+) -> Result<ConnectionReport, String> {
+    connection_stats(&pool, &query).await
 }
@@ -0,0 +1,47 @@
+use crate::ConfigState;
+use crate::files;
+use crate::commands::config_file_commands::get_config_file_path;
+use crate::watcher::{auto_import_directory, start_watcher, stop_watcher, watched_directory};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoImportStatus {
+    pub enabled: bool,
+    pub directory: Option<String>,
+}
+
+#[tauri::command]
+pub async fn set_auto_import(
+    app: tauri::AppHandle,
+    enabled: bool,
+    directory: Option<String>,
+) -> Result<AutoImportStatus, String> {
+    let path = get_config_file_path()?;
+    // Writing defaults over a config that failed to read would lose every other setting.
+    let mut cfg = files::read_json_file::<ConfigState>(path.clone()).await?;
+
+    cfg.auto_import_enabled = enabled;
+    // No directory keeps the one chosen before.
+    if let Some(directory) = directory.filter(|d| !d.trim().is_empty()) {
+        cfg.auto_import_directory = Some(directory);
+    }
+
+    if enabled {
+        start_watcher(app, auto_import_directory(&cfg)?)?;
+    } else {
+        stop_watcher()?;
+    }
+
+    files::write_json_file(path, &cfg).await?;
+
+    Ok(get_auto_import_status())
+}
+
+#[tauri::command]
+pub fn get_auto_import_status() -> AutoImportStatus {
+    let directory = watched_directory();
+    AutoImportStatus {
+        enabled: directory.is_some(),
+        directory,
+    }
+}
@@ -1,4 +1,8 @@
 use crate::ConfigState;
+use crate::analysis::fair_odds::FairOddsSettings;
+use crate::analysis::figures::FigureSettings;
+use crate::analysis::model_parameters::ModelParameters;
+use crate::analysis::trip_model::TripWeights;
 use crate::global_state;
 use crate::files;
 use crate::constants::HORSE_SORTING_METHOD_DEFAULT;
@@ -34,6 +38,12 @@ pub async fn load_config_file() -> Result<ConfigState, String> {
             window_width: None,
             window_height: None,
             horse_sorting_method: HORSE_SORTING_METHOD_DEFAULT.to_string(),
+            auto_import_enabled: false,
+            auto_import_directory: None,
+            trip_weights: TripWeights::default(),
+            model_parameters: ModelParameters::default(),
+            fair_odds: FairOddsSettings::default(),
+            figures: FigureSettings::default(),
         };
 
         files::write_json_file(&path, &cs).await?;
@@ -49,4 +59,81 @@ pub async fn load_config_file() -> Result<ConfigState, String> {
 pub async fn save_config_file(config_state: ConfigState) -> Result<(), String> {
     let path = get_config_file_path()?;
     files::write_json_file(path, &config_state).await
-}
\ No newline at end of file
+}
+
+// Reads config.json for callers outside the UI (exports, printouts, the backtest). None when there
+// is no config file yet or it can't be read, in which case callers fall back to defaults.
+async fn read_config_state() -> Option<ConfigState> {
+    let path = get_config_file_path().ok()?;
+    if !fs::try_exists(&path).await.unwrap_or(false) {
+        return None;
+    }
+
+    match files::read_json_file::<ConfigState>(path).await {
+        Ok(config_state) => Some(config_state),
+        Err(e) => {
+            eprintln!("Failed to read config file: {}", e);
+            None
+        }
+    }
+}
+
+pub async fn load_trip_weights() -> TripWeights {
+    read_config_state()
+        .await
+        .map(|config_state| config_state.trip_weights)
+        .unwrap_or_default()
+}
+
+pub async fn load_model_parameters() -> ModelParameters {
+    read_config_state()
+        .await
+        .map(|config_state| config_state.model_parameters)
+        .unwrap_or_default()
+}
+
+// Settings that don't validate (a takeout of 1 or more, a zero scale) fall back to the defaults.
+pub async fn load_fair_odds_settings() -> FairOddsSettings {
+    let settings = read_config_state()
+        .await
+        .map(|config_state| config_state.fair_odds)
+        .unwrap_or_default();
+    match settings.validate() {
+        Ok(()) => settings,
+        Err(e) => {
+            eprintln!("Ignoring fair odds settings in config: {}", e);
+            FairOddsSettings::default()
+        }
+    }
+}
+
+pub async fn load_figure_settings() -> FigureSettings {
+    let settings = read_config_state()
+        .await
+        .map(|config_state| config_state.figures)
+        .unwrap_or_default();
+    match settings.validate() {
+        Ok(()) => settings,
+        Err(e) => {
+            eprintln!("Ignoring figure settings in config: {}", e);
+            FigureSettings::default()
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_model_parameters() -> Result<ModelParameters, String> {
+    Ok(load_model_parameters().await)
+}
+
+// Only replaces the model parameters so the rest of the config is left as it is on disk.
+#[tauri::command]
+pub async fn save_model_parameters(model_parameters: ModelParameters) -> Result<ModelParameters, String> {
+    model_parameters.validate()?;
+
+    let path = get_config_file_path()?;
+    let mut config_state = load_config_file().await?;
+    config_state.model_parameters = model_parameters;
+    files::write_json_file(path, &config_state).await?;
+    Ok(config_state.model_parameters)
+}
@@ -0,0 +1,19 @@
+use sqlx::SqlitePool;
+use tauri::State;
+use crate::export::{export_racecard as write_export, ExportOptions, ExportReport};
+use crate::sqlite::racecards::read_racecard_by_id;
+
+// Exports from the database rather than the in-memory card so saved notes and scratches are included.
+#[tauri::command]
+pub async fn export_racecard(
+    pool: State<'_, SqlitePool>,
+    racecard_id: i64,
+    options: ExportOptions,
+    path: String,
+) -> Result<ExportReport, String> {
+    let racecard = read_racecard_by_id(&pool, racecard_id)
+        .await
+        .map_err(|e| format!("Failed to load racecard: {}", e))?;
+
+    write_export(&pool, &racecard, &options, &path).await
+}
@@ -5,3 +5,6 @@ pub mod process_racecard_file_commands;
 pub mod exit_app_command;
 pub mod print_racecard;
 pub mod analysis_commands;
+pub mod auto_import_commands;
+pub mod export_commands;
+pub mod track_commands;
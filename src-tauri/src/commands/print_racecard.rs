@@ -1,16 +1,49 @@
-use tauri::{menu::MenuItemKind, Manager};
+use sqlx::SqlitePool;
+use tauri::{menu::MenuItemKind, Manager, State};
+use crate::pdf::{write_bet_tickets_pdf, write_racecard_pdf, PdfReport};
+use crate::sqlite::bet_tickets::read_bet_tickets;
+use crate::sqlite::racecards::read_racecard_by_id;
 
+// Renders the stored card straight to a PDF file; no print webview is involved.
 #[tauri::command]
-pub fn print_racecard(_: tauri::AppHandle) {}
+pub async fn save_racecard_pdf(
+    pool: State<'_, SqlitePool>,
+    racecard_id: i64,
+    race_numbers: Option<Vec<i64>>,
+    path: String,
+) -> Result<PdfReport, String> {
+    let racecard = read_racecard_by_id(&pool, racecard_id)
+        .await
+        .map_err(|e| format!("Failed to load racecard: {}", e))?;
+
+    write_racecard_pdf(&racecard, race_numbers.as_deref(), &path).await
+}
+
+#[tauri::command]
+pub async fn save_bet_tickets_pdf(
+    pool: State<'_, SqlitePool>,
+    racecard_id: i64,
+    path: String,
+) -> Result<PdfReport, String> {
+    let racecard = read_racecard_by_id(&pool, racecard_id)
+        .await
+        .map_err(|e| format!("Failed to load racecard: {}", e))?;
+    let tickets = read_bet_tickets(&pool, racecard_id).await?;
+
+    write_bet_tickets_pdf(&racecard, &tickets, &path).await
+}
 
 #[tauri::command]
 pub fn set_print_racecard_enabled(app: tauri::AppHandle, enabled: bool) {
     if let Some(window) = app.get_webview_window("main") {
         if let Some(menu) = window.menu() {
             if let Some(MenuItemKind::Submenu(file_menu)) = menu.get("file") {
-                if let Some(item) = file_menu.get("print-racecard") {
-                    if let MenuItemKind::MenuItem(item) = item {
-                        let _ = item.set_enabled(enabled);
+                // Export needs a loaded racecard just like printing does.
+                for item_id in ["print-racecard", "export-racecard"] {
+                    if let Some(item) = file_menu.get(item_id) {
+                        if let MenuItemKind::MenuItem(item) = item {
+                            let _ = item.set_enabled(enabled);
+                        }
                     }
                 }
             }
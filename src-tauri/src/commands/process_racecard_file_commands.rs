@@ -1,6 +1,7 @@
 // use crate::files::write_json_file;
-use railbreaker_lib::build_racecard::build_racecard;
-use crate::sqlite::racecards::add_racecard;
+use crate::import::import_drf_file;
+use crate::sqlite::watch_list::notify_watched_horses;
+use crate::tracks::resolve_track_names;
 use sqlx::SqlitePool;
 use serde_json::Value;
 use tauri::AppHandle;
@@ -9,12 +10,9 @@ use tauri::Manager;
 
 #[tauri::command]
 pub async fn process_racecard_file(app: AppHandle, path: String, zip_file_name: String) -> Result<Value, String> {
-    let racecard = build_racecard(path, zip_file_name)
-        .await
-        .map_err(|e| e.to_string())?; 
-
-    let racecard = add_racecard(app.state::<SqlitePool>(), racecard).await
-        .map_err(|e| e.to_string())?;
+    let pool = app.state::<SqlitePool>();
+    let (racecard, watched) = import_drf_file(&pool, path, zip_file_name).await?;
+    notify_watched_horses(&app, &racecard, watched);
     //
     // Keep this for debugging purposes
     //
@@ -24,9 +22,9 @@ pub async fn process_racecard_file(app: AppHandle, path: String, zip_file_name:
     // let json_racecard_value = to_camel_case_value(json_racecard_value);
     // write_json_file(json_path, &json_racecard_value).await?;
 
-    let racecard_value = serde_json::to_value(&racecard)
+    let mut racecard_value = serde_json::to_value(&racecard)
         .map_err(|e| format!("Failed to serialize racecard: {}", e))?;
-    let racecard_value = racecard_value;
+    resolve_track_names(&mut racecard_value);
 
     Ok(racecard_value)
 }
\ No newline at end of file
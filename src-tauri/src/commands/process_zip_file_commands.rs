@@ -1,58 +1,392 @@
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use crate::sqlite::racecards::racecard_exists;
 use crate::states::global_state::global_state;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::State;
 use tokio::fs;
 
+// Brisnet single files are a few MB at most; anything far larger is not a racecard.
+const MAX_DRF_FILE_SIZE: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExtractStatus {
+    Extracted,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExtractedFile {
+    pub source: String,
+    pub file_name: String,
+    pub zip_file_name: String,
+    pub path: Option<String>,
+    pub status: ExtractStatus,
+    pub message: String,
+}
+
+impl ExtractedFile {
+    fn skipped(source: &str, file_name: &str, zip_file_name: String) -> Self {
+        Self {
+            source: source.to_string(),
+            file_name: file_name.to_string(),
+            zip_file_name,
+            path: None,
+            status: ExtractStatus::Skipped,
+            message: "Already in the database".to_string(),
+        }
+    }
+
+    fn duplicate(source: &str, file_name: &str, zip_file_name: String) -> Self {
+        Self {
+            message: "A file with the same name was already imported in this batch".to_string(),
+            ..Self::skipped(source, file_name, zip_file_name)
+        }
+    }
+
+    fn failed(source: &str, file_name: &str, zip_file_name: String, message: String) -> Self {
+        Self {
+            source: source.to_string(),
+            file_name: file_name.to_string(),
+            zip_file_name,
+            path: None,
+            status: ExtractStatus::Failed,
+            message,
+        }
+    }
+}
+
+// Names already handled in one import, so a folder holding the same card twice imports it once
+// and two cards never extract to the same file.
+#[derive(Debug, Default)]
+pub struct ExtractBatch {
+    card_names: HashSet<String>,
+    output_names: HashSet<String>,
+}
+
+impl ExtractBatch {
+    fn first_seen(&mut self, zip_file_name: &str) -> bool {
+        self.card_names.insert(zip_file_name.to_lowercase())
+    }
+}
+
 #[tauri::command]
-pub async fn process_zip_file(path: String) -> Result<String, String> {
-    let file = std::fs::File::open(&path).map_err(|e| format!("Failed to open zip file: {}", e))?;
+pub async fn process_zip_file(
+    pool: State<'_, SqlitePool>,
+    path: String,
+) -> Result<Vec<ExtractedFile>, String> {
+    extract_zip_file(&pool, &path, &mut ExtractBatch::default()).await
+}
+
+#[tauri::command]
+pub async fn process_racecard_directory(
+    pool: State<'_, SqlitePool>,
+    path: String,
+) -> Result<Vec<ExtractedFile>, String> {
+    extract_directory(&pool, &path).await
+}
+
+pub async fn extract_racecard_files(pool: &SqlitePool, path: &str) -> Result<Vec<ExtractedFile>, String> {
+    if Path::new(path).is_dir() {
+        extract_directory(pool, path).await
+    } else {
+        extract_zip_file(pool, path, &mut ExtractBatch::default()).await
+    }
+}
+
+pub async fn extract_zip_file(
+    pool: &SqlitePool,
+    path: &str,
+    batch: &mut ExtractBatch,
+) -> Result<Vec<ExtractedFile>, String> {
+    let archive_path = path.to_string();
+    let drf_entries = tauri::async_runtime::spawn_blocking(move || list_drf_entries(&archive_path))
+        .await
+        .map_err(|e| format!("Failed to read zip archive: {}", e))??;
+
+    let archive_name = file_name_of(Path::new(path));
+    if drf_entries.is_empty() {
+        return Err(format!("Zip archive {} does not contain any .DRF files", archive_name));
+    }
+
+    let racecards_dir = racecards_directory()?;
+    let single_file = drf_entries.len() == 1;
+    let mut statuses = Vec::with_capacity(drf_entries.len());
+    let mut wanted = Vec::new();
+
+    for DrfEntry { index, name: entry_name, file_name: enclosed_file_name, size } in drf_entries {
+        let file_name = match enclosed_file_name {
+            Some(name) => name,
+            None => {
+                statuses.push(ExtractedFile::failed(
+                    path,
+                    &entry_name,
+                    entry_name.clone(),
+                    "Entry has an unsafe path and was not extracted".to_string(),
+                ));
+                continue;
+            }
+        };
+        // Cards are keyed by the .DRF name wherever it came from, so the same card loose and zipped
+        // is stored once.
+        let zip_file_name = file_name.clone();
+
+        if !batch.first_seen(&zip_file_name) {
+            statuses.push(ExtractedFile::duplicate(path, &file_name, zip_file_name));
+            continue;
+        }
+
+        let mut exists = racecard_exists(pool, &zip_file_name)
+            .await
+            .map_err(|e| format!("Failed to check racecard: {}", e))?;
+        // Single-file zips used to be keyed by the zip name; those cards still count.
+        if !exists && single_file {
+            exists = racecard_exists(pool, &archive_name)
+                .await
+                .map_err(|e| format!("Failed to check racecard: {}", e))?;
+        }
+        if exists {
+            statuses.push(ExtractedFile::skipped(path, &file_name, zip_file_name));
+            continue;
+        }
+
+        if size > MAX_DRF_FILE_SIZE {
+            statuses.push(ExtractedFile::failed(
+                path,
+                &file_name,
+                zip_file_name,
+                format!("File is larger than {} bytes", MAX_DRF_FILE_SIZE),
+            ));
+            continue;
+        }
+
+        wanted.push((index, file_name, zip_file_name));
+    }
+
+    let archive_path = path.to_string();
+    let indexes: Vec<usize> = wanted.iter().map(|(index, _, _)| *index).collect();
+    let buffers = tauri::async_runtime::spawn_blocking(move || read_zip_entries(&archive_path, &indexes))
+        .await
+        .map_err(|e| format!("Failed to read zip archive: {}", e))??;
+
+    for ((_, file_name, zip_file_name), buffer) in wanted.into_iter().zip(buffers) {
+        match buffer {
+            Ok(buffer) => {
+                let outpath = unique_output_path(&racecards_dir, &file_name, &mut batch.output_names).await;
+                statuses.push(write_extracted(path, &file_name, zip_file_name, &outpath, buffer).await);
+            }
+            Err(e) => statuses.push(ExtractedFile::failed(path, &file_name, zip_file_name, e)),
+        }
+    }
+
+    Ok(statuses)
+}
+
+struct DrfEntry {
+    index: usize,
+    name: String,
+    // None when the entry's path is unsafe.
+    file_name: Option<String>,
+    size: u64,
+}
+
+fn list_drf_entries(path: &str) -> Result<Vec<DrfEntry>, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("Failed to open zip file: {}", e))?;
 
     let mut archive =
         zip::ZipArchive::new(file).map_err(|e| format!("Failed to read zip archive: {}", e))?;
 
-    if archive.len() == 0 || archive.len() > 1 {
-        return Err("Zip archive is not a valid single file archive".to_string());
+    let mut drf_entries = Vec::new();
+    for index in 0..archive.len() {
+        let entry = archive
+            .by_index(index)
+            .map_err(|e| format!("Failed to access file in zip: {}", e))?;
+        if !entry.is_dir() && is_drf_name(entry.name()) {
+            drf_entries.push(DrfEntry {
+                index,
+                name: entry.name().to_string(),
+                file_name: entry.enclosed_name().map(|name| file_name_of(&name)),
+                size: entry.size(),
+            });
+        }
     }
 
-    let mut file = archive
-        .by_index(0)
-        .map_err(|e| format!("Failed to access file in zip: {}", e))?;
+    Ok(drf_entries)
+}
 
-    let enclosed_name = file.enclosed_name();
-    let filename = match enclosed_name.as_ref() {
-        Some(path) => path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .ok_or("Invalid filename")?,
-        None => return Err("File has no valid enclosed name".to_string()),
-    };
+fn read_zip_entries(path: &str, indexes: &[usize]) -> Result<Vec<Result<Vec<u8>, String>>, String> {
+    if indexes.is_empty() {
+        return Ok(Vec::new());
+    }
 
-    if !filename.to_lowercase().ends_with(".drf") {
-        return Err(format!(
-            "File must have a .DRF extension, got: {}",
-            filename
-        ));
+    let file = std::fs::File::open(path).map_err(|e| format!("Failed to open zip file: {}", e))?;
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|e| format!("Failed to read zip archive: {}", e))?;
+
+    Ok(indexes
+        .iter()
+        .map(|index| {
+            let entry = archive
+                .by_index(*index)
+                .map_err(|e| format!("Failed to access file in zip: {}", e))?;
+            // The size in the zip header can't be trusted, so read one byte past the limit to tell a
+            // file that is too large from one that fits exactly.
+            let mut buffer = Vec::new();
+            entry
+                .take(MAX_DRF_FILE_SIZE + 1)
+                .read_to_end(&mut buffer)
+                .map_err(|e| format!("Failed to read file from zip: {}", e))?;
+            if buffer.len() as u64 > MAX_DRF_FILE_SIZE {
+                return Err(format!("File is larger than {} bytes", MAX_DRF_FILE_SIZE));
+            }
+            Ok(buffer)
+        })
+        .collect())
+}
+
+pub async fn extract_directory(pool: &SqlitePool, path: &str) -> Result<Vec<ExtractedFile>, String> {
+    let root = PathBuf::from(path);
+    if !root.is_dir() {
+        return Err(format!("{} is not a directory", path));
     }
 
-    let racecards_dir = {
-        let global_state = global_state()
-            .lock()
-            .map_err(|e| format!("Failed to lock global state: {}", e))?;
-        format!("{}/Racecards", global_state.current_directory)
-    };
+    let racecards_dir = racecards_directory()?;
+    let skip_dir = PathBuf::from(&racecards_dir);
+    let mut files = tauri::async_runtime::spawn_blocking(move || {
+        let mut files = Vec::new();
+        collect_racecard_files(&root, &skip_dir, &mut files).map(|_| files)
+    })
+    .await
+    .map_err(|e| format!("Failed to read directory '{}': {}", path, e))??;
+    files.sort();
 
-    let outpath = std::path::PathBuf::from(&racecards_dir).join(filename);
+    let mut batch = ExtractBatch::default();
+    let mut statuses = Vec::new();
 
-    if (*file.name()).ends_with('/') {
-        return Err(format!("Zip contains directories, which is not supported"));
-    } else {
-        use std::io::Read;
-        let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer)
-            .map_err(|e| format!("Failed to read file from zip: {}", e))?;
+    for file in files {
+        let source = file.to_string_lossy().to_string();
+        let file_name = file_name_of(&file);
 
-        fs::write(&outpath, buffer)
-            .await
-            .map_err(|e| format!("Failed to write file: {}", e))?;
+        if is_drf_name(&file_name) {
+            if !batch.first_seen(&file_name) {
+                statuses.push(ExtractedFile::duplicate(&source, &file_name, file_name.clone()));
+                continue;
+            }
+
+            if racecard_exists(pool, &file_name)
+                .await
+                .map_err(|e| format!("Failed to check racecard: {}", e))?
+            {
+                statuses.push(ExtractedFile::skipped(&source, &file_name, file_name.clone()));
+                continue;
+            }
+
+            match fs::read(&file).await {
+                Ok(buffer) => {
+                    let outpath = unique_output_path(&racecards_dir, &file_name, &mut batch.output_names).await;
+                    statuses.push(write_extracted(&source, &file_name, file_name.clone(), &outpath, buffer).await);
+                }
+                Err(e) => statuses.push(ExtractedFile::failed(
+                    &source,
+                    &file_name,
+                    file_name.clone(),
+                    format!("Failed to read file: {}", e),
+                )),
+            }
+        } else {
+            match extract_zip_file(pool, &source, &mut batch).await {
+                Ok(mut zip_statuses) => statuses.append(&mut zip_statuses),
+                Err(e) => statuses.push(ExtractedFile::failed(&source, &file_name, file_name.clone(), e)),
+            }
+        }
     }
 
-    Ok(outpath.to_string_lossy().to_string())
+    Ok(statuses)
+}
+
+fn collect_racecard_files(dir: &Path, racecards_dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory '{}': {}", dir.display(), e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let file_type = match entry.file_type() {
+            Ok(file_type) => file_type,
+            Err(_) => continue,
+        };
+
+        if file_type.is_dir() {
+            // Never re-import our own working copies.
+            if path != racecards_dir {
+                collect_racecard_files(&path, racecards_dir, files)?;
+            }
+        } else if file_type.is_file() {
+            let name = file_name_of(&path).to_lowercase();
+            if name.ends_with(".zip") || name.ends_with(".drf") {
+                files.push(path);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn write_extracted(
+    source: &str,
+    file_name: &str,
+    zip_file_name: String,
+    outpath: &Path,
+    buffer: Vec<u8>,
+) -> ExtractedFile {
+    match fs::write(outpath, buffer).await {
+        Ok(()) => ExtractedFile {
+            source: source.to_string(),
+            file_name: file_name.to_string(),
+            zip_file_name,
+            path: Some(outpath.to_string_lossy().to_string()),
+            status: ExtractStatus::Extracted,
+            message: String::new(),
+        },
+        Err(e) => ExtractedFile::failed(source, file_name, zip_file_name, format!("Failed to write file: {}", e)),
+    }
+}
+
+fn racecards_directory() -> Result<String, String> {
+    let global_state = global_state()
+        .lock()
+        .map_err(|e| format!("Failed to lock global state: {}", e))?;
+    Ok(format!("{}/Racecards", global_state.current_directory))
+}
+
+// Free in this batch and on disk, so an earlier import's working copy is never overwritten.
+async fn unique_output_path(racecards_dir: &str, file_name: &str, used_names: &mut HashSet<String>) -> PathBuf {
+    let path = Path::new(file_name);
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let extension = path.extension().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+
+    let mut candidate = file_name.to_string();
+    let mut counter = 2;
+    loop {
+        let outpath = PathBuf::from(racecards_dir).join(&candidate);
+        let on_disk = fs::try_exists(&outpath).await.unwrap_or(true);
+        if !on_disk && used_names.insert(candidate.to_lowercase()) {
+            return outpath;
+        }
+        candidate = format!("{}-{}.{}", stem, counter, extension);
+        counter += 1;
+    }
+}
+
+fn is_drf_name(name: &str) -> bool {
+    name.to_lowercase().ends_with(".drf")
+}
+
+fn file_name_of(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
 }
@@ -0,0 +1,23 @@
+use crate::states::global_state::global_state;
+use crate::tracks::{find_track, Track};
+
+#[tauri::command]
+pub fn get_tracks() -> Result<Vec<Track>, String> {
+    let gs = global_state()
+        .lock()
+        .map_err(|e| format!("Failed to lock global state: {}", e))?;
+
+    let mut tracks: Vec<Track> = gs.tracks.values().cloned().collect();
+    tracks.sort_by(|a, b| a.code.cmp(&b.code));
+    Ok(tracks)
+}
+
+// Accepts either the track's own code or one of its aliases.
+#[tauri::command]
+pub fn get_track(code: String) -> Result<Option<Track>, String> {
+    let gs = global_state()
+        .lock()
+        .map_err(|e| format!("Failed to lock global state: {}", e))?;
+
+    Ok(find_track(&gs.tracks, &code).cloned())
+}
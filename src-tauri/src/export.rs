@@ -0,0 +1,406 @@
+use railbreaker_lib::contextual_speed_and_pace_model::{HorseRank, RaceMeta};
+use railbreaker_lib::models::racecard::{Race, Racecard};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::SqlitePool;
+use crate::analysis::{distance_furlongs, enum_label, ranked_horses};
+use crate::analysis::class::{race_class, HorseClass, RaceClass};
+use crate::analysis::trip_model::{rank_race_trips, HorseTrip, TripRanking};
+use crate::analysis::model_parameters::rank_race_with_parameters;
+use crate::analysis::fair_odds::{fair_odds_line, FairOddsLine, HorseOdds};
+use crate::commands::config_file_commands::{load_fair_odds_settings, load_model_parameters, load_trip_weights};
+use crate::json::to_camel_case_value;
+use crate::sqlite::live_odds::current_odds;
+
+const RACE_FIELDS: &[&str] = &[
+    "race_number",
+    "distance",
+    "surface",
+    "race_type",
+    "todays_race_classification",
+    "age_sex_restrictions",
+    "purse",
+    "claiming_price",
+];
+
+const SUMMARY_FIELDS: &[&str] = &[
+    "program_number",
+    "post_position",
+    "horse_name",
+    "scratched",
+    "morning_line_odds",
+    "todays_trainer",
+    "todays_jockey",
+    "weight",
+    "days_since_last_race",
+    "bris_run_style",
+    "quirin_speed_points",
+    "bris_prime_power_rating",
+    "note",
+];
+
+const HANDICAPPING_FIELDS: &[&str] = &[
+    "sex",
+    "year_of_birth",
+    "sire",
+    "dam",
+    "equipment_change",
+    "todays_medication_new",
+    "trainer_starts",
+    "trainer_wins",
+    "jockey_starts",
+    "jockey_wins",
+    "trainer_jockey_combo_starts",
+    "trainer_jockey_combo_wins",
+    "trainer_jockey_combo_roi",
+    "lifetime_record_starts",
+    "lifetime_record_wins",
+    "lifetime_record_places",
+    "lifetime_record_shows",
+    "lifetime_record_earnings",
+    "lifetime_record_todays_distance_starts",
+    "lifetime_record_todays_distance_wins",
+    "lifetime_record_todays_track_starts",
+    "lifetime_record_todays_track_wins",
+    "lifetime_record_turf_starts",
+    "lifetime_record_turf_wins",
+    "lifetime_record_wet_starts",
+    "lifetime_record_wet_wins",
+    "current_year_record_starts",
+    "current_year_record_wins",
+    "current_year_record_earnings",
+    "trip_handicapping_info",
+];
+
+const MODEL_COLUMNS: &[&str] = &[
+    "model_rank",
+    "model_score",
+    "model_run_style",
+    "model_quirin",
+    "race_shape",
+    "race_epi",
+    "race_confidence",
+    "win_bet",
+    "win_bet_min_odds",
+    "trip_score",
+    "fair_probability",
+    "fair_odds",
+    "morning_line_value",
+    "class_level",
+    "class_change",
+    "class_move",
+    "claim_move",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldSet {
+    Summary,
+    #[default]
+    Handicapping,
+    // Every field on the card; in JSON this includes past performances and workouts.
+    Full,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    #[serde(default)]
+    pub field_set: FieldSet,
+    // All races when omitted.
+    #[serde(default)]
+    pub race_numbers: Option<Vec<i64>>,
+}
+
+// The model output exported for one race, computed once and shared by the CSV and JSON writers.
+pub struct RaceAnalysis<'a> {
+    pub race: &'a Race,
+    pub meta: RaceMeta,
+    pub trips: TripRanking,
+    pub odds: FairOddsLine,
+    pub class: RaceClass,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportReport {
+    pub path: String,
+    pub races: usize,
+    pub horses: usize,
+}
+
+pub async fn export_racecard(
+    pool: &SqlitePool,
+    racecard: &Racecard,
+    options: &ExportOptions,
+    path: &str,
+) -> Result<ExportReport, String> {
+    let races: Vec<&Race> = racecard
+        .races
+        .iter()
+        .filter(|race| match &options.race_numbers {
+            Some(numbers) => race.race_number.map(|n| numbers.contains(&(n as i64))).unwrap_or(false),
+            None => true,
+        })
+        .collect();
+
+    if races.is_empty() {
+        return Err("No matching races on this racecard".to_string());
+    }
+
+    let date = Some(racecard.date.as_str());
+    let parameters = load_model_parameters().await;
+    let weights = load_trip_weights().await;
+    let odds_settings = load_fair_odds_settings().await;
+    let mut analyses = Vec::with_capacity(races.len());
+    for race in &races {
+        let meta = rank_race_with_parameters(race, date, &parameters);
+        let live_odds = current_odds(pool, race.id).await?;
+        analyses.push(RaceAnalysis {
+            race,
+            odds: fair_odds_line(race, &meta, &odds_settings, &live_odds),
+            trips: rank_race_trips(race, date, &weights),
+            class: race_class(race),
+            meta,
+        });
+    }
+    let horses = races.iter().map(|race| race.horses.len()).sum();
+
+    match options.format {
+        ExportFormat::Csv => {
+            let (header, rows) = racecard_csv_rows(racecard, &analyses, options.field_set)?;
+            crate::files::write_csv_file(path, &header, &rows).await?;
+        }
+        ExportFormat::Json => {
+            let value = racecard_json(racecard, &analyses, options.field_set)?;
+            crate::files::write_json_file(path, &to_camel_case_value(value)).await?;
+        }
+    }
+
+    Ok(ExportReport {
+        path: path.to_string(),
+        races: races.len(),
+        horses,
+    })
+}
+
+// One row per horse, race columns repeated, so the file drops straight into a spreadsheet.
+pub fn racecard_csv_rows(
+    racecard: &Racecard,
+    analyses: &[RaceAnalysis],
+    field_set: FieldSet,
+) -> Result<(Vec<String>, Vec<Vec<String>>), String> {
+    let race_values = analyses.iter().map(|a| to_object(a.race)).collect::<Result<Vec<_>, _>>()?;
+    let race_fields = match field_set {
+        FieldSet::Full => scalar_keys(&race_values),
+        _ => RACE_FIELDS.iter().map(|f| f.to_string()).collect(),
+    };
+
+    let horse_values = analyses
+        .iter()
+        .map(|a| a.race.horses.iter().map(to_object).collect::<Result<Vec<_>, _>>())
+        .collect::<Result<Vec<_>, _>>()?;
+    let horse_fields = match field_set {
+        FieldSet::Summary => SUMMARY_FIELDS.iter().map(|f| f.to_string()).collect(),
+        FieldSet::Handicapping => SUMMARY_FIELDS
+            .iter()
+            .chain(HANDICAPPING_FIELDS)
+            .map(|f| f.to_string())
+            .collect(),
+        FieldSet::Full => scalar_keys(&horse_values.concat()),
+    };
+
+    let mut header = vec!["track_code".to_string(), "date".to_string(), "distance_furlongs".to_string()];
+    header.extend(race_fields.iter().map(|f| format!("race_{}", f.trim_start_matches("race_"))));
+    header.extend(horse_fields.iter().cloned());
+    header.extend(MODEL_COLUMNS.iter().map(|c| c.to_string()));
+
+    let mut rows = Vec::new();
+    for ((analysis, race_value), horses) in analyses.iter().zip(&race_values).zip(&horse_values) {
+        let RaceAnalysis { race, meta, trips, odds, class } = analysis;
+        let ranked = ranked_horses(meta);
+        let furlongs = distance_furlongs(race.distance.map(|d| d as f64))
+            .map(|f| format!("{:.2}", f))
+            .unwrap_or_default();
+
+        for (horse, horse_value) in race.horses.iter().zip(horses) {
+            let mut row = vec![racecard.track_code.clone(), racecard.date.clone(), furlongs.clone()];
+            row.extend(race_fields.iter().map(|f| cell(race_value.get(f))));
+            row.extend(horse_fields.iter().map(|f| cell(horse_value.get(f))));
+            row.extend(model_cells(meta, &ranked, &horse.program_number));
+            row.push(
+                horse_trip(trips, horse.id)
+                    .map(|trip| format!("{:.1}", trip.score))
+                    .unwrap_or_default(),
+            );
+            let horse_odds = horse_odds(odds, horse.id);
+            row.extend([
+                horse_odds.and_then(|o| o.probability).map(|p| format!("{:.3}", p)).unwrap_or_default(),
+                horse_odds.and_then(|o| o.fair_odds).map(|o| format!("{:.1}", o)).unwrap_or_default(),
+                horse_odds.and_then(|o| o.morning_line_value).map(|v| enum_label(&v)).unwrap_or_default(),
+            ]);
+            let horse_class = horse_class(class, horse.id);
+            row.extend([
+                class.level.map(|l| format!("{:.1}", l)).unwrap_or_default(),
+                horse_class.and_then(|c| c.class_change).map(|c| format!("{:.1}", c)).unwrap_or_default(),
+                horse_class.and_then(|c| c.class_move).map(|m| enum_label(&m)).unwrap_or_default(),
+                horse_class.and_then(|c| c.claim_move).map(|m| enum_label(&m)).unwrap_or_default(),
+            ]);
+            rows.push(row);
+        }
+    }
+
+    Ok((header, rows))
+}
+
+pub fn racecard_json(
+    racecard: &Racecard,
+    analyses: &[RaceAnalysis],
+    field_set: FieldSet,
+) -> Result<Value, String> {
+    let mut race_entries = Vec::with_capacity(analyses.len());
+
+    for RaceAnalysis { race, meta, trips, odds, class } in analyses {
+        let race_value = to_object(race)?;
+        let mut entry = match field_set {
+            FieldSet::Full => race_value,
+            _ => pick(&race_value, RACE_FIELDS.iter().copied()),
+        };
+        entry.insert(
+            "distance_furlongs".to_string(),
+            serde_json::json!(distance_furlongs(race.distance.map(|d| d as f64))),
+        );
+        entry.insert("class_level".to_string(), serde_json::json!(class.level));
+
+        let ranked = ranked_horses(meta);
+        let mut horses = Vec::with_capacity(race.horses.len());
+        for horse in &race.horses {
+            let horse_value = to_object(horse)?;
+            let mut horse_entry = match field_set {
+                FieldSet::Summary => pick(&horse_value, SUMMARY_FIELDS.iter().copied()),
+                FieldSet::Handicapping => pick(&horse_value, SUMMARY_FIELDS.iter().chain(HANDICAPPING_FIELDS).copied()),
+                FieldSet::Full => horse_value,
+            };
+            let rank = ranked
+                .iter()
+                .position(|r| r.program_number == horse.program_number)
+                .map(|idx| (idx + 1, ranked[idx]));
+            horse_entry.insert("model_rank".to_string(), serde_json::json!(rank.map(|(n, _)| n)));
+            horse_entry.insert(
+                "model".to_string(),
+                serde_json::to_value(rank.map(|(_, r)| r))
+                    .map_err(|e| format!("Failed to serialize horse rank: {}", e))?,
+            );
+            horse_entry.insert(
+                "trip".to_string(),
+                serde_json::to_value(horse_trip(trips, horse.id))
+                    .map_err(|e| format!("Failed to serialize trip analysis: {}", e))?,
+            );
+            horse_entry.insert(
+                "fair_odds".to_string(),
+                serde_json::to_value(horse_odds(odds, horse.id))
+                    .map_err(|e| format!("Failed to serialize fair odds: {}", e))?,
+            );
+            horse_entry.insert(
+                "class".to_string(),
+                serde_json::to_value(horse_class(class, horse.id))
+                    .map_err(|e| format!("Failed to serialize class analysis: {}", e))?,
+            );
+            horses.push(Value::Object(horse_entry));
+        }
+
+        entry.insert("horses".to_string(), Value::Array(horses));
+        entry.insert(
+            "meta".to_string(),
+            serde_json::to_value(meta).map_err(|e| format!("Failed to serialize race analysis: {}", e))?,
+        );
+        race_entries.push(Value::Object(entry));
+    }
+
+    Ok(serde_json::json!({
+        "id": racecard.id,
+        "track_code": racecard.track_code,
+        "track": racecard.track,
+        "date": racecard.date,
+        "long_date": racecard.long_date,
+        "field_set": field_set,
+        "races": race_entries,
+    }))
+}
+
+fn horse_trip(trips: &TripRanking, horse_id: i64) -> Option<&HorseTrip> {
+    trips.horses.iter().find(|trip| trip.horse_id == horse_id)
+}
+
+fn horse_odds(odds: &FairOddsLine, horse_id: i64) -> Option<&HorseOdds> {
+    odds.horses.iter().find(|o| o.horse_id == horse_id)
+}
+
+fn horse_class(class: &RaceClass, horse_id: i64) -> Option<&HorseClass> {
+    class.horses.iter().find(|c| c.horse_id == horse_id)
+}
+
+fn model_cells(meta: &RaceMeta, ranked: &[&HorseRank], program_number: &str) -> Vec<String> {
+    let rank = ranked.iter().position(|r| r.program_number == program_number);
+    let horse = rank.map(|idx| ranked[idx]);
+    let win_bet = meta
+        .win_bet
+        .as_ref()
+        .filter(|bet| bet.program_number == program_number);
+
+    vec![
+        rank.map(|idx| (idx + 1).to_string()).unwrap_or_default(),
+        horse.and_then(|h| h.score).map(|s| format!("{:.2}", s)).unwrap_or_default(),
+        horse.map(|h| enum_label(&h.run_style)).unwrap_or_default(),
+        horse.and_then(|h| h.quirin).map(|q| q.to_string()).unwrap_or_default(),
+        enum_label(&meta.shape),
+        format!("{:.2}", meta.epi),
+        enum_label(&meta.confidence),
+        if win_bet.is_some() { "Y".to_string() } else { String::new() },
+        win_bet
+            .and_then(|bet| bet.min_odds)
+            .map(|o| format!("{:.1}", o))
+            .unwrap_or_default(),
+    ]
+}
+
+pub fn to_object<T: Serialize>(value: &T) -> Result<Map<String, Value>, String> {
+    match serde_json::to_value(value) {
+        Ok(Value::Object(map)) => Ok(map),
+        Ok(_) => Err("Expected an object when serializing racecard".to_string()),
+        Err(e) => Err(format!("Failed to serialize racecard: {}", e)),
+    }
+}
+
+fn pick<'a>(value: &Map<String, Value>, fields: impl Iterator<Item = &'a str>) -> Map<String, Value> {
+    fields
+        .map(|f| (f.to_string(), value.get(f).cloned().unwrap_or(Value::Null)))
+        .collect()
+}
+
+// Nested lists (past performances, workouts, horses) do not fit in a CSV cell and are left out.
+fn scalar_keys(values: &[Map<String, Value>]) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    for value in values {
+        for (key, field) in value {
+            if !matches!(field, Value::Array(_) | Value::Object(_)) && !keys.contains(key) {
+                keys.push(key.clone());
+            }
+        }
+    }
+    keys
+}
+
+fn cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::OnceLock;
 use serde::{de::DeserializeOwned, Serialize};
@@ -47,4 +48,112 @@ where
                 .map_err(|e2| format!("Failed to rename temp file '{}' -> '{}' (rename error: {}), and failed to write directly: {}", tmp.display(), path.display(), e, e2))
         }
     }
+}
+
+// A data row keyed by lowercase header, with the 1-based line it came from for error messages.
+#[derive(Debug, Clone)]
+pub struct CsvRow {
+    pub line: usize,
+    pub fields: HashMap<String, String>,
+}
+
+impl CsvRow {
+    pub fn get(&self, column: &str) -> Option<&String> {
+        self.fields.get(column)
+    }
+}
+
+pub async fn read_csv_file(path: impl Into<PathBuf>) -> Result<Vec<CsvRow>, String> {
+    let path = path.into();
+
+    let content = fs::read_to_string(&path)
+        .await
+        .map_err(|e| format!("Failed to read file '{}': {}", path.display(), e))?;
+
+    parse_csv(&content).map_err(|e| format!("Failed to parse CSV file '{}': {}", path.display(), e))
+}
+
+// Written with a UTF-8 BOM and CRLF line endings so Excel opens accented names correctly.
+pub async fn write_csv_file(path: impl Into<PathBuf>, header: &[String], rows: &[Vec<String>]) -> Result<(), String> {
+    let path = path.into();
+
+    let mut content = String::from("\u{feff}");
+    for line in std::iter::once(header).chain(rows.iter().map(|row| row.as_slice())) {
+        let fields: Vec<String> = line.iter().map(|field| escape_csv_field(field)).collect();
+        content.push_str(&fields.join(","));
+        content.push_str("\r\n");
+    }
+
+    fs::write(&path, content)
+        .await
+        .map_err(|e| format!("Failed to write file '{}': {}", path.display(), e))
+}
+
+fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub fn parse_csv(content: &str) -> Result<Vec<CsvRow>, String> {
+    let mut lines = content
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.trim_end_matches('\r')))
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'));
+
+    let header: Vec<String> = match lines.next() {
+        Some((_, line)) => split_csv_line(line)
+            .into_iter()
+            .map(|h| h.trim().to_lowercase())
+            .collect(),
+        None => return Ok(Vec::new()),
+    };
+
+    let mut rows = Vec::new();
+    for (line_number, line) in lines {
+        let fields = split_csv_line(line);
+        if fields.len() > header.len() {
+            return Err(format!(
+                "Line {} has {} columns, expected {}",
+                line_number,
+                fields.len(),
+                header.len()
+            ));
+        }
+
+        let fields = header
+            .iter()
+            .cloned()
+            .zip(fields.into_iter().map(|f| f.trim().to_string()).chain(std::iter::repeat(String::new())))
+            .collect();
+        rows.push(CsvRow { line: line_number, fields });
+    }
+
+    Ok(rows)
+}
+
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(ch),
+        }
+    }
+    fields.push(field);
+
+    fields
 }
\ No newline at end of file
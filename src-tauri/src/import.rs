@@ -0,0 +1,83 @@
+use railbreaker_lib::build_racecard::build_racecard;
+use railbreaker_lib::models::racecard::Racecard;
+use sqlx::SqlitePool;
+use crate::commands::process_zip_file_commands::{extract_racecard_files, ExtractStatus, ExtractedFile};
+use crate::sqlite::pp_lines::index_pp_lines;
+use crate::sqlite::search::index_racecard;
+use crate::sqlite::watch_list::{apply_watch_notes, watched_horses_on_racecard, WatchAlert};
+
+pub struct ImportedFile {
+    pub file: ExtractedFile,
+    pub racecard: Option<Racecard>,
+    // Watch-listed horses entered on the imported card.
+    pub watched: Vec<WatchAlert>,
+}
+
+// Runs the same extract -> build_racecard -> add_racecard pipeline as the "Open Zip..." menu
+// for a zip (single or multi-file) or a folder of zips and .DRF files.
+pub async fn import_racecard_files(pool: &SqlitePool, path: &str) -> Result<Vec<ImportedFile>, String> {
+    let extracted = extract_racecard_files(pool, path).await?;
+
+    let mut imported = Vec::with_capacity(extracted.len());
+    for mut file in extracted {
+        let drf_path = match (&file.status, &file.path) {
+            (ExtractStatus::Extracted, Some(drf_path)) => drf_path.clone(),
+            _ => {
+                imported.push(ImportedFile { file, racecard: None, watched: Vec::new() });
+                continue;
+            }
+        };
+
+        match import_drf_file(pool, drf_path, file.zip_file_name.clone()).await {
+            Ok((racecard, watched)) => imported.push(ImportedFile { file, racecard: Some(racecard), watched }),
+            Err(e) => {
+                file.status = ExtractStatus::Failed;
+                file.message = e;
+                imported.push(ImportedFile { file, racecard: None, watched: Vec::new() });
+            }
+        }
+    }
+
+    Ok(imported)
+}
+
+pub async fn import_drf_file(
+    pool: &SqlitePool,
+    drf_path: String,
+    zip_file_name: String,
+) -> Result<(Racecard, Vec<WatchAlert>), String> {
+    let racecard = build_racecard(drf_path, zip_file_name)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut racecard = railbreaker_lib::sqlite::racecards::add_racecard(pool, racecard)
+        .await
+        .map_err(|e| format!("Failed to add racecard: {}", e))?;
+
+    let watched = after_racecard_added(pool, &mut racecard).await;
+
+    Ok((racecard, watched))
+}
+
+// Bookkeeping that runs for every newly stored card, however it was imported. The card itself is
+// already saved, so failures are only logged; anything missed here is caught up at the next
+// startup. Returns the watch-listed horses on the card for the caller to alert on.
+pub async fn after_racecard_added(pool: &SqlitePool, racecard: &mut Racecard) -> Vec<WatchAlert> {
+    if let Err(e) = index_racecard(pool, racecard).await {
+        eprintln!("Failed to index racecard {}: {}", racecard.id, e);
+        return Vec::new();
+    }
+
+    if let Err(e) = apply_watch_notes(pool, racecard).await {
+        eprintln!("Failed to carry watch list notes to racecard {}: {}", racecard.id, e);
+    }
+
+    if let Err(e) = index_pp_lines(pool, racecard).await {
+        eprintln!("Failed to collect past performances for racecard {}: {}", racecard.id, e);
+    }
+
+    watched_horses_on_racecard(pool, racecard.id).await.unwrap_or_else(|e| {
+        eprintln!("Failed to check watch list for racecard {}: {}", racecard.id, e);
+        Vec::new()
+    })
+}
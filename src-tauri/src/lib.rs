@@ -1,34 +1,59 @@
+mod analysis;
+mod betting;
+mod cli;
 mod commands;
 mod constants;
+mod export;
 mod files;
+mod import;
 mod json;
 mod menus;
+mod pdf;
 mod states;
 mod sqlite;
+mod tracks;
+mod watcher;
 
 use tauri::{Emitter, Manager};
 #[cfg(desktop)]
 use tauri_plugin_global_shortcut::GlobalShortcutExt;
-use constants::HORSE_SORTING_METHOD_DEFAULT;
 use commands::global_state_commands::load_global_state;
-use commands::config_file_commands::{load_config_file, save_config_file, get_config_file_path};
-use commands::process_zip_file_commands::process_zip_file;
+use commands::config_file_commands::{
+    load_config_file, save_config_file, get_config_file_path, get_model_parameters, save_model_parameters,
+};
+use commands::process_zip_file_commands::{process_racecard_directory, process_zip_file};
 use commands::print_racecard::{
     close_print_window,
     hide_print_window_menu,
-    print_racecard,
+    save_bet_tickets_pdf,
+    save_racecard_pdf,
     set_print_racecard_enabled,
     set_view_menu_enabled,
 };
 use commands::process_racecard_file_commands::process_racecard_file;
-use commands::analysis_commands::rank_race;
+use commands::analysis_commands::{
+    get_connection_stats, get_fair_odds, get_figures, get_form_cycles, get_race_class, get_racecard_bias, get_track_bias,
+    get_workout_report, rank_race, rank_trips, run_backtest,
+};
 use commands::exit_app_command::exit_app;
+use commands::export_commands::export_racecard;
+use commands::track_commands::{get_track, get_tracks};
+use commands::auto_import_commands::{get_auto_import_status, set_auto_import};
 use sqlite::racecards::{
     add_racecard, get_all_racecards, get_racecard_by_id, racecard_exists_by_zip_name, set_scratch, update_note,
 };
+use sqlite::bet_tickets::{delete_bet_ticket, get_bet_tickets, preview_bet_ticket, save_bet_ticket};
+use sqlite::horse_history::get_horse_history;
+use sqlite::live_odds::{get_live_odds, import_live_odds_file, set_live_odds};
+use sqlite::maintenance::{archive_racecards, clean_racecard_files, delete_racecard, optimize_database};
+use sqlite::results::{get_race_results, import_results_file, set_race_result};
+use sqlite::search::search_racecards;
+use sqlite::watch_list::{add_to_watch_list, get_watch_list, get_watch_status, remove_from_watch_list};
 use states::config_state::ConfigState;
 use states::global_state::global_state;
-use railbreaker_lib::sqlite::racecards::create_tables;
+
+pub use analysis::backtest::run_headless as run_backtest_headless;
+pub use cli::run as run_cli;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -72,11 +97,28 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             load_config_file,
             save_config_file,
-            print_racecard,
+            get_model_parameters,
+            save_model_parameters,
+            save_racecard_pdf,
+            save_bet_tickets_pdf,
             load_global_state,
             process_zip_file,
+            process_racecard_directory,
             process_racecard_file,
             rank_race,
+            rank_trips,
+            get_fair_odds,
+            get_figures,
+            get_race_class,
+            get_form_cycles,
+            get_workout_report,
+            run_backtest,
+            get_track_bias,
+            get_racecard_bias,
+            get_connection_stats,
+            export_racecard,
+            get_tracks,
+            get_track,
             set_print_racecard_enabled,
             set_view_menu_enabled,
             close_print_window,
@@ -88,6 +130,28 @@ pub fn run() {
             racecard_exists_by_zip_name,
             update_note,
             set_scratch,
+            set_race_result,
+            import_results_file,
+            get_race_results,
+            delete_racecard,
+            archive_racecards,
+            clean_racecard_files,
+            optimize_database,
+            search_racecards,
+            add_to_watch_list,
+            remove_from_watch_list,
+            get_watch_list,
+            get_watch_status,
+            get_horse_history,
+            preview_bet_ticket,
+            save_bet_ticket,
+            get_bet_tickets,
+            delete_bet_ticket,
+            set_live_odds,
+            import_live_odds_file,
+            get_live_odds,
+            set_auto_import,
+            get_auto_import_status,
         ])
         .run(context)
         .expect("error while running tauri application");
@@ -107,10 +171,13 @@ fn register_global_shortcuts(app: &tauri::App) {
         });
 
         let _ = gs.on_shortcut("CmdOrCtrl+Shift+P", move |app, _shortcut, _event| {
-            print_racecard(app.clone());
             let _ = app.emit("menu-print", ());
         });
 
+        let _ = gs.on_shortcut("CmdOrCtrl+Shift+E", move |app, _shortcut, _event| {
+            let _ = app.emit("menu-export", ());
+        });
+
         let _ = gs.on_shortcut("CmdOrCtrl+N", move |app, _shortcut, _event| {
             let _ = app.emit("menu-next-page", ());
         });
@@ -157,14 +224,24 @@ fn init_setup(app: &tauri::App) -> Result<(), String> {
         gs.racecards_directory = racecard_path.to_string_lossy().to_string();
     }
 
+    if let Err(e) = tracks::init_tracks(&current_directory) {
+        eprintln!("{}", e);
+    }
+
     let database_url = sqlite::get_database_file_path()?;
     
-    let pool = tauri::async_runtime::block_on(async {
-        let pool = sqlite::db::make_pool(&database_url).await?;
-        create_tables(&pool).await?;
-        Ok::<_, anyhow::Error>(pool)
-    })
-    .map_err(|e| format!("Failed to initialize database: {}", e))?;
+    let pool = tauri::async_runtime::block_on(sqlite::db::make_pool(&database_url))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    // A failed migration should not stop the app from starting; the UI reports it from global state.
+    match tauri::async_runtime::block_on(sqlite::prepare_database(&pool, &database_url)) {
+        Ok(version) => global_state.lock().unwrap().schema_version = version,
+        Err(e) => {
+            eprintln!("{}", e);
+            global_state.lock().unwrap().database_error = Some(e);
+        }
+    }
+
     app.manage(pool);
 
     Ok(())
@@ -174,6 +251,14 @@ async fn load_or_init_config(app_handle: tauri::AppHandle) {
     let path = get_config_file_path().unwrap();
 
     if let Ok(cfg) = files::read_json_file::<ConfigState>(path.clone()).await {
+        if cfg.auto_import_enabled {
+            if let Err(e) = watcher::auto_import_directory(&cfg)
+                .and_then(|directory| watcher::start_watcher(app_handle.clone(), directory))
+            {
+                eprintln!("Failed to start auto-import: {}", e);
+            }
+        }
+
         if let Some(window) = app_handle.get_webview_window("main") {
             if let (Some(w), Some(h)) = (cfg.window_width, cfg.window_height) {
                 let _ = window.set_size(tauri::Size::Logical(tauri::LogicalSize {
@@ -208,7 +293,7 @@ async fn load_or_init_config(app_handle: tauri::AppHandle) {
             window_y: Some(y),
             window_width: Some(width),
             window_height: Some(height),
-            horse_sorting_method: HORSE_SORTING_METHOD_DEFAULT.to_string(),
+            ..ConfigState::default()
         };
 
         let _ = files::write_json_file(path, &cfg).await;
@@ -264,13 +349,21 @@ fn on_menu_event(app: &tauri::AppHandle, event: tauri::menu::MenuEvent) {
         "open-zip" => {
             let _ = app.emit("menu-open-zip", ()).unwrap();
         }
+        "import-folder" => {
+            let _ = app.emit("menu-import-folder", ()).unwrap();
+        }
+        "auto-import" => {
+            let _ = app.emit("menu-auto-import", ()).unwrap();
+        }
         "exit" => {
             let _ = app.emit("menu-exit", ()).unwrap();
         }
         "print-racecard" => {
-            print_racecard(app.clone());
             let _ = app.emit("menu-print", ()).unwrap();
         }
+        "export-racecard" => {
+            let _ = app.emit("menu-export", ()).unwrap();
+        }
         "next-page" => {
             let _ = app.emit("menu-next-page", ()).unwrap();
         }
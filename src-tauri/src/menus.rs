@@ -3,9 +3,13 @@ use tauri::{menu::{Menu, MenuItem, Submenu, PredefinedMenuItem}, App};
 pub fn setup_menus(app: &App) -> tauri::Result<()> {
     let open = MenuItem::with_id(app, "open", "Open Racecard…", true, Some("CmdOrCtrl+O"))?;
     let open_zip = MenuItem::with_id(app, "open-zip", "Open Zip…", true, Some("CmdOrCtrl+Shift+O"))?;
+    let import_folder = MenuItem::with_id(app, "import-folder", "Import Folder…", true, None::<&str>)?;
+    let auto_import = MenuItem::with_id(app, "auto-import", "Auto-Import Folder…", true, None::<&str>)?;
     let separator0 = PredefinedMenuItem::separator(app)?;
     let print_racecard = MenuItem::with_id(app, "print-racecard", "Print Racecard…", true, Some("CmdOrCtrl+Shift+P"))?;
     let _ = print_racecard.set_enabled(false);
+    let export_racecard = MenuItem::with_id(app, "export-racecard", "Export Racecard…", true, Some("CmdOrCtrl+Shift+E"))?;
+    let _ = export_racecard.set_enabled(false);
     let separator1 = PredefinedMenuItem::separator(app)?;
     let quit = MenuItem::with_id(app, "exit", "Exit", true, Some("CmdOrCtrl+Q"))?;
     let about = MenuItem::with_id(app, "about", "About RailBreaker", true, None::<&str>)?;
@@ -24,7 +28,7 @@ pub fn setup_menus(app: &App) -> tauri::Result<()> {
         "file",
         "File",
         true,
-        &[&open, &open_zip, &separator0, &print_racecard, &separator1, &quit],
+        &[&open, &open_zip, &import_folder, &auto_import, &separator0, &print_racecard, &export_racecard, &separator1, &quit],
     )?;
     let view_menu = Submenu::with_id_and_items(
         app,
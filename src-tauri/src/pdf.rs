@@ -0,0 +1,805 @@
+use railbreaker_lib::contextual_speed_and_pace_model::{HorseRank, RaceMeta};
+use railbreaker_lib::models::racecard::{Horse, Race, Racecard};
+use pdf_writer::{Content, Name, Pdf, Rect, Ref, Str};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use crate::analysis::{enum_label, ranked_horses, surface_label, YARDS_PER_FURLONG};
+use crate::betting::{ticket_legs_text, BetTicket, TicketStructure};
+use crate::analysis::form_cycle::{form_pattern_label, race_form_cycles, HorseForm, RaceForm};
+use crate::analysis::trip_model::{rank_race_trips, HorseTrip, TripRanking, TripWeights};
+use crate::analysis::model_parameters::{rank_race_with_parameters, ModelParameters};
+use crate::commands::config_file_commands::{load_model_parameters, load_trip_weights};
+use crate::export::to_object;
+
+// US Letter, landscape, so a full past performance line fits across the page.
+const PAGE_WIDTH: f32 = 792.0;
+const PAGE_HEIGHT: f32 = 612.0;
+const MARGIN: f32 = 28.0;
+const FOOTER_HEIGHT: f32 = 14.0;
+// Only the built-in Courier faces are used: every glyph is 600/1000 em wide, so column layout
+// is plain string padding and the output is identical on every machine.
+const CHAR_WIDTH: f32 = 0.6;
+const LINE_SPACING: f32 = 1.2;
+
+const TITLE_SIZE: f32 = 11.0;
+const RACE_SIZE: f32 = 9.0;
+const HORSE_SIZE: f32 = 8.0;
+const BODY_SIZE: f32 = 6.5;
+
+const YARDS_PER_MILE: f64 = 1760.0;
+const MAX_PAST_PERFORMANCES: usize = 10;
+const MAX_WORKOUTS: usize = 12;
+// Continuation lines of a PP comment start under the track column.
+const PP_COMMENT_INDENT: usize = 11;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PdfReport {
+    pub path: String,
+    pub pages: usize,
+    pub bytes: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Style {
+    Regular,
+    Bold,
+    Muted,
+    Alert,
+}
+
+struct TextRun {
+    x: f32,
+    y: f32,
+    size: f32,
+    style: Style,
+    text: String,
+}
+
+#[derive(Default)]
+struct Page {
+    runs: Vec<TextRun>,
+    rules: Vec<f32>,
+}
+
+struct Layout {
+    title: String,
+    pages: Vec<Page>,
+    y: f32,
+}
+
+impl Layout {
+    fn new(title: String) -> Self {
+        let mut layout = Layout { title, pages: Vec::new(), y: 0.0 };
+        layout.new_page();
+        layout
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(Page::default());
+        self.y = PAGE_HEIGHT - MARGIN;
+        let title = self.title.clone();
+        self.line(&title, TITLE_SIZE, Style::Bold);
+        self.rule();
+    }
+
+    fn page(&mut self) -> &mut Page {
+        self.pages.last_mut().expect("layout always has a page")
+    }
+
+    fn fits(&self, height: f32) -> bool {
+        self.y - height >= MARGIN + FOOTER_HEIGHT
+    }
+
+    // Starts a new page unless `height` points still fit, so blocks are not split across pages.
+    fn keep_together(&mut self, height: f32) {
+        if !self.fits(height) {
+            self.new_page();
+        }
+    }
+
+    fn line(&mut self, text: &str, size: f32, style: Style) {
+        self.keep_together(size * LINE_SPACING);
+        self.y -= size * LINE_SPACING;
+        let y = self.y;
+        self.page().runs.push(TextRun { x: MARGIN, y, size, style, text: text.to_string() });
+    }
+
+    fn lines(&mut self, lines: &[(String, f32, Style)]) {
+        for (text, size, style) in lines {
+            self.line(text, *size, *style);
+        }
+    }
+
+    fn rule(&mut self) {
+        self.y -= 3.0;
+        let y = self.y;
+        self.page().rules.push(y);
+        self.y -= 1.0;
+    }
+
+    fn gap(&mut self, height: f32) {
+        self.y -= height;
+    }
+}
+
+pub async fn write_racecard_pdf(racecard: &Racecard, race_numbers: Option<&[i64]>, path: &str) -> Result<PdfReport, String> {
+    let weights = load_trip_weights().await;
+    let parameters = load_model_parameters().await;
+    let (bytes, pages) = render_racecard_pdf(racecard, race_numbers, &weights, &parameters)?;
+    write_pdf(path, bytes, pages).await
+}
+
+pub async fn write_bet_tickets_pdf(racecard: &Racecard, tickets: &[BetTicket], path: &str) -> Result<PdfReport, String> {
+    let (bytes, pages) = render_bet_tickets_pdf(racecard, tickets)?;
+    write_pdf(path, bytes, pages).await
+}
+
+async fn write_pdf(path: &str, bytes: Vec<u8>, pages: usize) -> Result<PdfReport, String> {
+    tokio::fs::write(path, &bytes)
+        .await
+        .map_err(|e| format!("Failed to write PDF '{}': {}", path, e))?;
+
+    Ok(PdfReport {
+        path: path.to_string(),
+        pages,
+        bytes: bytes.len(),
+    })
+}
+
+// Renders the selected races (all when `race_numbers` is None) and returns the PDF with its page count.
+pub fn render_racecard_pdf(
+    racecard: &Racecard,
+    race_numbers: Option<&[i64]>,
+    trip_weights: &TripWeights,
+    model_parameters: &ModelParameters,
+) -> Result<(Vec<u8>, usize), String> {
+    let races: Vec<&Race> = racecard
+        .races
+        .iter()
+        .filter(|race| match race_numbers {
+            Some(numbers) => race.race_number.map(|n| numbers.contains(&(n as i64))).unwrap_or(false),
+            None => true,
+        })
+        .collect();
+
+    if races.is_empty() {
+        return Err("No matching races on this racecard".to_string());
+    }
+
+    let mut layout = Layout::new(format!("{}  {}", racecard.track, racecard.long_date));
+
+    for (idx, race) in races.iter().enumerate() {
+        if idx > 0 {
+            layout.new_page();
+        }
+        let meta = rank_race_with_parameters(race, Some(racecard.date.as_str()), model_parameters);
+        let trips = rank_race_trips(race, Some(racecard.date.as_str()), trip_weights);
+        let form = race_form_cycles(race);
+        layout_race(&mut layout, race, &meta, &trips, &form)?;
+    }
+
+    let pages = layout.pages.len();
+    Ok((render(&layout), pages))
+}
+
+// One block per saved ticket, in race order, with the card's total outlay at the end.
+pub fn render_bet_tickets_pdf(racecard: &Racecard, tickets: &[BetTicket]) -> Result<(Vec<u8>, usize), String> {
+    if tickets.is_empty() {
+        return Err("There are no bet tickets for this racecard".to_string());
+    }
+
+    let max_chars = max_chars(BODY_SIZE);
+    let mut layout = Layout::new(format!("{}  {}  Bet Tickets", racecard.track, racecard.long_date));
+
+    for ticket in tickets {
+        let races = if ticket.bet_type.is_multi_race() {
+            format!("Races {}-{}", ticket.race_number, ticket.race_number + ticket.bet_type.legs() as i64 - 1)
+        } else {
+            format!("Race {}", ticket.race_number)
+        };
+        let mut block = vec![(
+            format!(
+                "{}  {}  {}  ${:.2} base",
+                races,
+                ticket.bet_type.label(),
+                structure_label(ticket.structure),
+                ticket.base_amount
+            ),
+            HORSE_SIZE,
+            Style::Bold,
+        )];
+        for line in wrap(&ticket_legs_text(ticket), max_chars) {
+            block.push((line, BODY_SIZE, Style::Regular));
+        }
+        block.push((
+            format!("{} combinations  ${:.2}", ticket.combinations, ticket.total_cost),
+            BODY_SIZE,
+            Style::Regular,
+        ));
+        for line in wrap(&ticket.note, max_chars) {
+            block.push((line, BODY_SIZE, Style::Muted));
+        }
+
+        let height: f32 = block.iter().map(|(_, size, _)| size * LINE_SPACING).sum();
+        layout.keep_together(height + 5.0);
+        layout.lines(&block);
+        layout.rule();
+        layout.gap(1.0);
+    }
+
+    let total: f64 = tickets.iter().map(|t| t.total_cost).sum();
+    layout.line(&format!("{} tickets  Total ${:.2}", tickets.len(), total), HORSE_SIZE, Style::Bold);
+
+    let pages = layout.pages.len();
+    Ok((render(&layout), pages))
+}
+
+fn structure_label(structure: TicketStructure) -> &'static str {
+    match structure {
+        TicketStructure::PartWheel => "Part-wheel",
+        TicketStructure::Box => "Box",
+        TicketStructure::KeyWith => "Key/with",
+    }
+}
+
+fn layout_race(
+    layout: &mut Layout,
+    race: &Race,
+    meta: &RaceMeta,
+    trips: &TripRanking,
+    form: &RaceForm,
+) -> Result<(), String> {
+    let race_value = to_object(race)?;
+    let max_chars = max_chars(BODY_SIZE);
+
+    layout.line(
+        &format!(
+            "Race {}  {}  {}  {}  Purse {}{}",
+            text(&race_value, "race_number"),
+            race.distance.map(|d| format_distance(d as f64)).unwrap_or_default(),
+            surface_label(&race.surface),
+            race.todays_race_classification,
+            text(&race_value, "purse"),
+            match text(&race_value, "claiming_price").as_str() {
+                "" | "0" => String::new(),
+                price => format!("  Claiming {}", price),
+            }
+        ),
+        RACE_SIZE,
+        Style::Bold,
+    );
+
+    let conditions: Vec<String> = (1..=6)
+        .map(|n| text(&race_value, &format!("race_conditions_line{}", n)))
+        .filter(|line| !line.is_empty())
+        .collect();
+    let conditions = if conditions.is_empty() { race.race_conditions.clone() } else { conditions.join(" ") };
+    for line in wrap(&conditions, max_chars) {
+        layout.line(&line, BODY_SIZE, Style::Muted);
+    }
+
+    layout.line(&model_summary(meta), BODY_SIZE, Style::Regular);
+    layout.rule();
+
+    let ranked = ranked_horses(meta);
+    for horse in &race.horses {
+        let trip = trips.horses.iter().find(|trip| trip.horse_id == horse.id);
+        let horse_form = form.horses.iter().find(|form| form.horse_id == horse.id);
+        let block = horse_block(horse, &ranked, trip, horse_form, max_chars)?;
+        let height: f32 = block.iter().map(|(_, size, _)| size * LINE_SPACING).sum();
+        layout.keep_together(height + 5.0);
+        layout.lines(&block);
+        layout.rule();
+        layout.gap(1.0);
+    }
+
+    Ok(())
+}
+
+fn model_summary(meta: &RaceMeta) -> String {
+    let mut summary = format!(
+        "Model  Shape: {}  EPI: {:.2}  Confidence: {}",
+        enum_label(&meta.shape),
+        meta.epi,
+        enum_label(&meta.confidence)
+    );
+
+    if let Some(win_bet) = &meta.win_bet {
+        summary.push_str(&format!(
+            "  Win bet: #{} {}{}",
+            win_bet.program_number,
+            win_bet.horse_name,
+            win_bet
+                .min_odds
+                .map(|o| format!(" at {:.1}-1 or better", o))
+                .unwrap_or_default()
+        ));
+    }
+
+    summary
+}
+
+fn horse_block(
+    horse: &Horse,
+    ranked: &[&HorseRank],
+    trip: Option<&HorseTrip>,
+    form: Option<&HorseForm>,
+    max_chars: usize,
+) -> Result<Vec<(String, f32, Style)>, String> {
+    let value = to_object(horse)?;
+    let mut block = Vec::new();
+
+    let rank = ranked
+        .iter()
+        .position(|r| r.program_number == horse.program_number)
+        .map(|idx| (idx + 1, ranked[idx]));
+
+    let mut headline = format!(
+        "{:>3}  {}  ({} {})  ML {}  Wt {}",
+        horse.program_number,
+        horse.horse_name,
+        text(&value, "sex"),
+        text(&value, "year_of_birth"),
+        text(&value, "morning_line_odds"),
+        text(&value, "weight"),
+    );
+    match rank {
+        Some((position, horse_rank)) => headline.push_str(&format!(
+            "  Model #{} {} ({})",
+            position,
+            horse_rank.score.map(|s| format!("{:.1}", s)).unwrap_or_default(),
+            enum_label(&horse_rank.run_style)
+        )),
+        None if horse.scratched => headline.push_str("  SCRATCHED"),
+        None => {}
+    }
+    block.push((headline, HORSE_SIZE, if horse.scratched { Style::Alert } else { Style::Bold }));
+
+    block.push((
+        format!(
+            "Trainer: {} ({})  Jockey: {} ({})  Owner: {}  Style: {} {}  Prime Power: {}  Days off: {}",
+            horse.todays_trainer,
+            record(&value, "trainer_starts", "trainer_wins"),
+            horse.todays_jockey,
+            record(&value, "jockey_starts", "jockey_wins"),
+            horse.todays_owner,
+            text(&value, "bris_run_style"),
+            text(&value, "quirin_speed_points"),
+            text(&value, "bris_prime_power_rating"),
+            text(&value, "days_since_last_race"),
+        ),
+        BODY_SIZE,
+        Style::Regular,
+    ));
+
+    block.push((
+        format!(
+            "{} - {} ({})  Life {}  Dist {}  Trk {}  Turf {}  Wet {}  Med {}  Eqp {}",
+            horse.sire,
+            horse.dam,
+            text(&value, "dams_sire"),
+            full_record(&value, "lifetime_record"),
+            full_record(&value, "lifetime_record_todays_distance"),
+            full_record(&value, "lifetime_record_todays_track"),
+            full_record(&value, "lifetime_record_turf"),
+            full_record(&value, "lifetime_record_wet"),
+            text(&value, "todays_medication_new"),
+            text(&value, "equipment_change"),
+        ),
+        BODY_SIZE,
+        Style::Muted,
+    ));
+
+    let past_performances: Vec<Map<String, Value>> = value
+        .get("past_performances")
+        .and_then(Value::as_array)
+        .map(|pps| pps.iter().filter_map(|pp| pp.as_object().cloned()).collect())
+        .unwrap_or_default();
+    let past_performances: Vec<&Map<String, Value>> = past_performances
+        .iter()
+        .filter(|pp| !text(pp, "race_date").is_empty())
+        .take(MAX_PAST_PERFORMANCES)
+        .collect();
+
+    if !past_performances.is_empty() {
+        block.push((pp_header(), BODY_SIZE, Style::Muted));
+        for pp in past_performances {
+            for line in pp_lines(pp, max_chars) {
+                block.push((line, BODY_SIZE, Style::Regular));
+            }
+        }
+    }
+
+    let workouts: Vec<String> = value
+        .get("workouts")
+        .and_then(Value::as_array)
+        .map(|workouts| {
+            workouts
+                .iter()
+                .filter_map(Value::as_object)
+                .filter(|w| !text(w, "date").is_empty())
+                .take(MAX_WORKOUTS)
+                .map(workout_text)
+                .collect()
+        })
+        .unwrap_or_default();
+    if !workouts.is_empty() {
+        for line in wrap(&format!("Works: {}", workouts.join("   ")), max_chars) {
+            block.push((line, BODY_SIZE, Style::Muted));
+        }
+    }
+
+    if let Some(trip) = trip.filter(|trip| !trip.trips.is_empty()) {
+        for line in wrap(&trip_summary(trip), max_chars) {
+            block.push((line, BODY_SIZE, Style::Regular));
+        }
+    }
+
+    if let Some(form) = form.filter(|form| !form.labels.is_empty()) {
+        for line in wrap(&form_summary(form), max_chars) {
+            block.push((line, BODY_SIZE, Style::Regular));
+        }
+    }
+
+    if !horse.note.trim().is_empty() {
+        for line in horse.note.trim().lines().flat_map(|line| wrap(line, max_chars.saturating_sub(6))) {
+            block.push((format!("Note: {}", line), BODY_SIZE, Style::Bold));
+        }
+    }
+
+    Ok(block)
+}
+
+fn trip_summary(trip: &HorseTrip) -> String {
+    let lines: Vec<String> = trip
+        .trips
+        .iter()
+        .map(|line| {
+            let days = line.days_back.map(|d| format!("{}d ", d)).unwrap_or_default();
+            let outlook = enum_label(&line.outlook);
+            if line.comment.is_empty() {
+                format!("{}{}", days, outlook)
+            } else {
+                format!("{}{}: {}", days, outlook, line.comment)
+            }
+        })
+        .collect();
+    format!("Trip {:.0}: {}", trip.score, lines.join(" | "))
+}
+
+fn form_summary(form: &HorseForm) -> String {
+    let labels: Vec<String> = form
+        .labels
+        .iter()
+        .map(|label| format!("{} ({})", form_pattern_label(label.pattern), label.evidence))
+        .collect();
+    format!("Form: {}", labels.join(" | "))
+}
+
+fn pp_header() -> String {
+    format!(
+        "{:<10} {:<6} {:<9} {:<16} {:>3} {:>3} {:>3} {:>3} {:>2} {:>3} {:>5} {:>5} {:>5} {:>5}  {:<16} {:>6}  {:<44} {}",
+        "DATE", "TRK", "DIST", "RACETYPE", "E1", "E2", "LP", "SPD", "PP", "ST", "1C", "2C", "STR", "FIN",
+        "JOCKEY", "ODDS", "TOP FINISHERS", "COMMENT"
+    )
+}
+
+// The columns fill most of the width, so the trip comment carries on below, indented past the date.
+fn pp_lines(pp: &Map<String, Value>, max_chars: usize) -> Vec<String> {
+    let columns = pp_columns(pp);
+    let mut lines = vec![truncate(&columns, max_chars)];
+    let indent = " ".repeat(PP_COMMENT_INDENT);
+
+    for word in text(pp, "trip_comment").split_whitespace() {
+        let last = lines.last_mut().expect("lines starts with the columns");
+        let width = last.chars().count();
+        if width + 1 + word.chars().count() <= max_chars {
+            last.push(' ');
+            last.push_str(word);
+        } else {
+            lines.push(format!("{}{}", indent, word));
+        }
+    }
+
+    lines
+}
+
+fn pp_columns(pp: &Map<String, Value>) -> String {
+    let distance = number(pp, "distance");
+    let (e1, e2) = match distance.map(f64::abs) {
+        // Same pace calls as the on-screen card: 2f/4f for sprints, 4f/6f for routes.
+        Some(d) if d >= YARDS_PER_MILE => (text(pp, "bris_4f_pace"), text(pp, "bris_6f_pace")),
+        _ if number(pp, "bris_4f_pace").unwrap_or(0.0) == 0.0 => (String::new(), text(pp, "bris_2f_pace")),
+        _ => (text(pp, "bris_2f_pace"), text(pp, "bris_4f_pace")),
+    };
+
+    let finishers = [("winners_name", "winners_margin"), ("place_name", "place_margin"), ("show_name", "show_margin")]
+        .iter()
+        .map(|(name, margin)| format!("{} {}", text(pp, name), text(pp, margin)).trim().to_string())
+        .filter(|f| !f.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "{:<10} {:<6} {:<9} {:<16} {:>3} {:>3} {:>3} {:>3} {:>2} {:>3} {:>5} {:>5} {:>5} {:>5}  {:<16} {:>6}  {:<44}",
+        text(pp, "race_date"),
+        format!("{}{}", text(pp, "track_code"), text(pp, "race_number")),
+        format!(
+            "{} {}{}",
+            distance.map(format_distance).unwrap_or_default(),
+            text(pp, "surface"),
+            text(pp, "track_condition").to_lowercase()
+        ),
+        truncate(&text(pp, "race_classication"), 16),
+        e1,
+        e2,
+        text(pp, "bris_late_pace"),
+        text(pp, "bris_speed_rating"),
+        text(pp, "post_position"),
+        text(pp, "start_call_position"),
+        call(pp, "first_call_position", "first_call_between_lengths"),
+        call(pp, "second_call_position", "second_call_between_lengths"),
+        call(pp, "stretch_call_position", "stretch_call_between_lengths"),
+        call(pp, "finish_position", "finish_between_lengths"),
+        truncate(&text(pp, "jockey"), 16),
+        text(pp, "odds"),
+        truncate(&finishers, 44),
+    )
+}
+
+fn workout_text(workout: &Map<String, Value>) -> String {
+    let bullet = if number(workout, "rank") == Some(1.0) { "*" } else { "" };
+    format!(
+        "{}{} {} {} {} {} {} {}/{}",
+        bullet,
+        text(workout, "date"),
+        text(workout, "track"),
+        number(workout, "distance").map(format_distance).unwrap_or_default(),
+        text(workout, "condition"),
+        number(workout, "time").map(format_time).unwrap_or_default(),
+        text(workout, "description"),
+        text(workout, "rank"),
+        text(workout, "workouts_that_day_distance"),
+    )
+}
+
+fn call(pp: &Map<String, Value>, position: &str, lengths: &str) -> String {
+    let position = text(pp, position);
+    match number(pp, lengths) {
+        Some(l) if l > 0.0 && !position.is_empty() => format!("{} {}", position, format_lengths(l)),
+        _ => position,
+    }
+}
+
+fn record(value: &Map<String, Value>, starts: &str, wins: &str) -> String {
+    match (number(value, starts), number(value, wins)) {
+        (Some(s), Some(w)) if s > 0.0 => format!("{}-{} {:.0}%", s, w, w / s * 100.0),
+        _ => "-".to_string(),
+    }
+}
+
+fn full_record(value: &Map<String, Value>, prefix: &str) -> String {
+    format!(
+        "{}-{}-{}-{}",
+        text(value, &format!("{}_starts", prefix)),
+        text(value, &format!("{}_wins", prefix)),
+        text(value, &format!("{}_places", prefix)),
+        text(value, &format!("{}_shows", prefix)),
+    )
+}
+
+fn number(value: &Map<String, Value>, key: &str) -> Option<f64> {
+    match value.get(key) {
+        Some(Value::Number(n)) => n.as_f64(),
+        Some(Value::String(s)) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+// Whole numbers print without a trailing ".0" whatever numeric type the model field uses.
+fn text(value: &Map<String, Value>, key: &str) -> String {
+    match value.get(key) {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.trim().to_string(),
+        Some(Value::Number(n)) => match n.as_f64() {
+            Some(f) if f.fract() == 0.0 => format!("{}", f as i64),
+            _ => n.to_string(),
+        },
+        Some(other) => other.to_string(),
+    }
+}
+
+// Brisnet distances are yards; negative means "about" the distance, shown with a trailing '*'.
+fn format_distance(yards: f64) -> String {
+    let about = if yards < 0.0 { "*" } else { "" };
+    let yards = yards.abs();
+
+    let distance = match yards.round() as i64 {
+        0 => return String::new(),
+        1800 => "1m40y".to_string(),
+        1830 => "1m70y".to_string(),
+        _ if yards >= YARDS_PER_MILE => format!("{}m", with_fraction(yards / YARDS_PER_MILE, 16)),
+        _ => format!("{}f", with_fraction(yards / YARDS_PER_FURLONG, 2)),
+    };
+
+    format!("{}{}", distance, about)
+}
+
+fn with_fraction(value: f64, denominator: i64) -> String {
+    let whole = value.trunc() as i64;
+    let mut numerator = ((value - value.trunc()) * denominator as f64).round() as i64;
+    let mut denominator = denominator;
+
+    if numerator == 0 {
+        return whole.to_string();
+    }
+    if numerator == denominator {
+        return (whole + 1).to_string();
+    }
+    while numerator % 2 == 0 && denominator % 2 == 0 {
+        numerator /= 2;
+        denominator /= 2;
+    }
+
+    if whole == 0 {
+        format!("{}/{}", numerator, denominator)
+    } else {
+        format!("{} {}/{}", whole, numerator, denominator)
+    }
+}
+
+fn format_time(seconds: f64) -> String {
+    let seconds = seconds.abs();
+    let minutes = (seconds / 60.0).floor();
+    let remaining = seconds - minutes * 60.0;
+    if minutes > 0.0 {
+        format!("{}:{:05.2}", minutes as i64, remaining)
+    } else {
+        format!("{:.2}", remaining)
+    }
+}
+
+fn format_lengths(lengths: f64) -> String {
+    match lengths {
+        l if l < 0.1 => "nose".to_string(),
+        l if l < 0.2 => "hd".to_string(),
+        l if l < 0.3 => "nk".to_string(),
+        l => with_fraction(l, 4),
+    }
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    text.chars().take(max).collect()
+}
+
+fn max_chars(size: f32) -> usize {
+    ((PAGE_WIDTH - 2.0 * MARGIN) / (size * CHAR_WIDTH)) as usize
+}
+
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for word in text.split_whitespace() {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    lines
+}
+
+// The standard fonts use WinAnsiEncoding, which matches Latin-1 for accented letters.
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c {
+            '\u{20}'..='\u{7e}' | '\u{a0}'..='\u{ff}' => c as u8,
+            '\u{2018}' => 0x91,
+            '\u{2019}' => 0x92,
+            '\u{201c}' => 0x93,
+            '\u{201d}' => 0x94,
+            '\u{2022}' => 0x95,
+            '\u{2013}' => 0x96,
+            '\u{2014}' => 0x97,
+            _ => b'?',
+        })
+        .collect()
+}
+
+fn render(layout: &Layout) -> Vec<u8> {
+    let mut pdf = Pdf::new();
+    let catalog_id = Ref::new(1);
+    let tree_id = Ref::new(2);
+    let regular_id = Ref::new(3);
+    let bold_id = Ref::new(4);
+    let info_id = Ref::new(5);
+    let first_page = 6;
+
+    let page_count = layout.pages.len();
+    let page_ids: Vec<Ref> = (0..page_count).map(|i| Ref::new(first_page + 2 * i as i32)).collect();
+
+    pdf.catalog(catalog_id).pages(tree_id);
+    pdf.pages(tree_id).kids(page_ids.iter().copied()).count(page_count as i32);
+    pdf.type1_font(regular_id)
+        .base_font(Name(b"Courier"))
+        .encoding_predefined(Name(b"WinAnsiEncoding"));
+    pdf.type1_font(bold_id)
+        .base_font(Name(b"Courier-Bold"))
+        .encoding_predefined(Name(b"WinAnsiEncoding"));
+    // No creation date, so the same card always produces byte-identical output.
+    pdf.document_info(info_id)
+        .title(pdf_writer::TextStr(&layout.title))
+        .producer(pdf_writer::TextStr("RailBreaker"));
+
+    for (idx, page) in layout.pages.iter().enumerate() {
+        let page_id = page_ids[idx];
+        let content_id = Ref::new(page_id.get() + 1);
+
+        let mut content = Content::new();
+
+        content.set_line_width(0.4);
+        content.set_stroke_gray(0.6);
+        for y in &page.rules {
+            content.move_to(MARGIN, *y);
+            content.line_to(PAGE_WIDTH - MARGIN, *y);
+            content.stroke();
+        }
+
+        for run in &page.runs {
+            show_text(&mut content, run);
+        }
+        show_text(
+            &mut content,
+            &TextRun {
+                x: MARGIN,
+                y: MARGIN,
+                size: BODY_SIZE,
+                style: Style::Muted,
+                text: format!("RailBreaker  {}  Page {} of {}", layout.title, idx + 1, page_count),
+            },
+        );
+
+        pdf.stream(content_id, &content.finish());
+
+        let mut pdf_page = pdf.page(page_id);
+        pdf_page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT));
+        pdf_page.parent(tree_id);
+        pdf_page.contents(content_id);
+        pdf_page
+            .resources()
+            .fonts()
+            .pair(Name(b"F1"), regular_id)
+            .pair(Name(b"F2"), bold_id);
+    }
+
+    pdf.finish()
+}
+
+fn show_text(content: &mut Content, run: &TextRun) {
+    let (font, gray, red) = match run.style {
+        Style::Regular => (Name(b"F1"), 0.0, false),
+        Style::Bold => (Name(b"F2"), 0.0, false),
+        Style::Muted => (Name(b"F1"), 0.35, false),
+        Style::Alert => (Name(b"F2"), 0.0, true),
+    };
+
+    if red {
+        content.set_fill_rgb(0.75, 0.1, 0.1);
+    } else {
+        content.set_fill_gray(gray);
+    }
+
+    let bytes = win_ansi(&run.text);
+    content.begin_text();
+    content.set_font(font, run.size);
+    content.next_line(run.x, run.y);
+    content.show(Str(&bytes));
+    content.end_text();
+}
@@ -0,0 +1,19 @@
+use sqlx::SqlitePool;
+
+// (path, size, modified) of every zip the watcher has finished with.
+pub async fn read_handled_zips(pool: &SqlitePool) -> Result<Vec<(String, i64, i64)>, sqlx::Error> {
+    sqlx::query_as("SELECT path, size, modified FROM auto_import_zips;")
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn mark_zip_handled(pool: &SqlitePool, path: &str, size: i64, modified: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT OR REPLACE INTO auto_import_zips (path, size, modified) VALUES (?, ?, ?);")
+        .bind(path)
+        .bind(size)
+        .bind(modified)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+use tauri::State;
+use crate::analysis::enum_label;
+use crate::betting::{build_ticket, BetTicket, TicketRequest};
+use crate::commands::config_file_commands::load_model_parameters;
+use crate::sqlite::racecards::read_racecard_by_id;
+
+// Works out the combinations and cost without saving, for the ticket builder as it is edited.
+#[tauri::command]
+pub async fn preview_bet_ticket(
+    pool: State<'_, SqlitePool>,
+    racecard_id: i64,
+    request: TicketRequest,
+) -> Result<BetTicket, String> {
+    price_ticket(&pool, racecard_id, &request).await
+}
+
+// Saves a new ticket, or replaces the ticket with `ticket_id`.
+#[tauri::command]
+pub async fn save_bet_ticket(
+    pool: State<'_, SqlitePool>,
+    racecard_id: i64,
+    request: TicketRequest,
+    ticket_id: Option<i64>,
+) -> Result<BetTicket, String> {
+    let ticket = price_ticket(&pool, racecard_id, &request).await?;
+    store_bet_ticket(&pool, ticket, ticket_id).await
+}
+
+#[tauri::command]
+pub async fn get_bet_tickets(pool: State<'_, SqlitePool>, racecard_id: i64) -> Result<Vec<BetTicket>, String> {
+    read_bet_tickets(&pool, racecard_id).await
+}
+
+#[tauri::command]
+pub async fn delete_bet_ticket(pool: State<'_, SqlitePool>, ticket_id: i64) -> Result<(), String> {
+    sqlx::query("DELETE FROM bet_tickets WHERE id = ?;")
+        .bind(ticket_id)
+        .execute(&*pool)
+        .await
+        .map_err(|e| format!("Failed to delete bet ticket: {}", e))?;
+
+    Ok(())
+}
+
+pub async fn price_ticket(pool: &SqlitePool, racecard_id: i64, request: &TicketRequest) -> Result<BetTicket, String> {
+    let racecard = read_racecard_by_id(pool, racecard_id)
+        .await
+        .map_err(|e| format!("Failed to load racecard: {}", e))?;
+    let parameters = load_model_parameters().await;
+    build_ticket(racecard_id, &racecard, request, &parameters)
+}
+
+pub async fn store_bet_ticket(pool: &SqlitePool, mut ticket: BetTicket, ticket_id: Option<i64>) -> Result<BetTicket, String> {
+    let selections = serde_json::to_string(&ticket.selections)
+        .map_err(|e| format!("Failed to serialize selections: {}", e))?;
+    let legs = serde_json::to_string(&ticket.legs).map_err(|e| format!("Failed to serialize legs: {}", e))?;
+    let created_at = chrono::Utc::now().to_rfc3339();
+
+    // An edit only reaches a ticket on the same racecard; the racecard_id binds last for it.
+    let query = match ticket_id {
+        Some(_) => {
+            "UPDATE bet_tickets SET
+                bet_type = ?, race_number = ?, base_amount = ?, structure = ?,
+                selections = ?, legs = ?, combinations = ?, total_cost = ?, note = ?
+             WHERE id = ? AND racecard_id = ?
+             RETURNING id, created_at;"
+        }
+        None => {
+            "INSERT INTO bet_tickets (
+                bet_type, race_number, base_amount, structure,
+                selections, legs, combinations, total_cost, note, created_at, racecard_id
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING id, created_at;"
+        }
+    };
+
+    let query = sqlx::query(query)
+        .bind(enum_label(&ticket.bet_type))
+        .bind(ticket.race_number)
+        .bind(ticket.base_amount)
+        .bind(enum_label(&ticket.structure))
+        .bind(selections)
+        .bind(legs)
+        .bind(ticket.combinations)
+        .bind(ticket.total_cost)
+        .bind(&ticket.note);
+    let query = match ticket_id {
+        Some(id) => query.bind(id),
+        None => query.bind(created_at),
+    }
+    .bind(ticket.racecard_id);
+
+    let row = query
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to save bet ticket: {}", e))?
+        .ok_or_else(|| {
+            format!(
+                "Bet ticket {} does not exist on racecard {}",
+                ticket_id.unwrap_or_default(),
+                ticket.racecard_id
+            )
+        })?;
+
+    ticket.id = Some(row.get("id"));
+    ticket.created_at = Some(row.get("created_at"));
+    Ok(ticket)
+}
+
+pub async fn read_bet_tickets(pool: &SqlitePool, racecard_id: i64) -> Result<Vec<BetTicket>, String> {
+    let rows = sqlx::query(
+        "SELECT * FROM bet_tickets WHERE racecard_id = ? ORDER BY race_number ASC, id ASC;",
+    )
+    .bind(racecard_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load bet tickets: {}", e))?;
+
+    rows.iter().map(ticket_from_row).collect()
+}
+
+fn ticket_from_row(row: &SqliteRow) -> Result<BetTicket, String> {
+    Ok(BetTicket {
+        id: Some(row.get("id")),
+        racecard_id: row.get("racecard_id"),
+        bet_type: from_label(row.get("bet_type"))?,
+        race_number: row.get("race_number"),
+        base_amount: row.get("base_amount"),
+        structure: from_label(row.get("structure"))?,
+        selections: from_json(row.get("selections"))?,
+        legs: from_json(row.get("legs"))?,
+        combinations: row.get("combinations"),
+        total_cost: row.get("total_cost"),
+        note: row.get("note"),
+        created_at: Some(row.get("created_at")),
+    })
+}
+
+fn from_label<T: DeserializeOwned>(label: String) -> Result<T, String> {
+    serde_json::from_value(Value::String(label)).map_err(|e| format!("Failed to read bet ticket: {}", e))
+}
+
+fn from_json<T: DeserializeOwned>(json: String) -> Result<T, String> {
+    serde_json::from_str(&json).map_err(|e| format!("Failed to read bet ticket: {}", e))
+}
@@ -17,8 +17,20 @@ pub async fn make_pool(database_url: &str) -> Result<SqlitePool> {
         .connect_with(opts)
         .await?;
 
-    // Run migrations at startup
-    //sqlx::migrate!("./migrations").run(&pool).await?;
+    Ok(pool)
+}
+
+// For tools that only read: no file is created and nothing is migrated.
+pub async fn make_read_only_pool(database_path: &str) -> Result<SqlitePool> {
+    let opts = SqliteConnectOptions::new()
+        .filename(database_path)
+        .read_only(true)
+        .busy_timeout(std::time::Duration::from_secs(5));
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(5)
+        .connect_with(opts)
+        .await?;
 
     Ok(pool)
 }
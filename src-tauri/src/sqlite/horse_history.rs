@@ -0,0 +1,150 @@
+use std::collections::HashSet;
+use railbreaker_lib::models::racecard::{PastPerformance, Workout};
+use serde::Serialize;
+use sqlx::{Row, SqlitePool};
+use tauri::State;
+use crate::sqlite::pp_lines::pp_date;
+use crate::sqlite::racecards::read_racecard_by_id;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HorseAppearance {
+    pub racecard_id: i64,
+    pub horse_id: i64,
+    pub date: String,
+    pub track_code: String,
+    pub race_number: Option<i64>,
+    pub program_number: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HorseHistory {
+    pub horse_key: String,
+    pub horse_name: String,
+    pub year_of_birth: Option<i64>,
+    pub sire: String,
+    pub dam: String,
+    // Every stored card the horse is entered on, newest first.
+    pub appearances: Vec<HorseAppearance>,
+    // Merged from all of those cards, newest first like the lines on a card.
+    pub past_performances: Vec<PastPerformance>,
+    pub workouts: Vec<Workout>,
+}
+
+#[tauri::command]
+pub async fn get_horse_history(pool: State<'_, SqlitePool>, horse_id: i64) -> Result<HorseHistory, String> {
+    let horse_key = sqlx::query_scalar::<_, String>("SELECT horse_key FROM search_entries WHERE horse_id = ?;")
+        .bind(horse_id)
+        .fetch_optional(&*pool)
+        .await
+        .map_err(|e| format!("Failed to load horse: {}", e))?
+        .ok_or_else(|| format!("Horse {} is not in the database", horse_id))?;
+
+    read_horse_history(&pool, &horse_key).await
+}
+
+// Each card carries its own copy of the horse's lines; a line seen on several cards is kept once,
+// from the newest card, which has the most complete comments and figures.
+pub async fn read_horse_history(pool: &SqlitePool, horse_key: &str) -> Result<HorseHistory, String> {
+    let rows = sqlx::query(
+        "SELECT racecard_id, horse_id, date, track_code, race_number, program_number
+         FROM search_entries
+         WHERE horse_key = ?
+         ORDER BY date DESC, racecard_id DESC;",
+    )
+    .bind(horse_key)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load horse appearances: {}", e))?;
+
+    let appearances: Vec<HorseAppearance> = rows
+        .iter()
+        .map(|row| HorseAppearance {
+            racecard_id: row.get("racecard_id"),
+            horse_id: row.get("horse_id"),
+            date: row.get("date"),
+            track_code: row.get("track_code"),
+            race_number: row.get("race_number"),
+            program_number: row.get("program_number"),
+        })
+        .collect();
+
+    let mut history = HorseHistory {
+        horse_key: horse_key.to_string(),
+        horse_name: String::new(),
+        year_of_birth: None,
+        sire: String::new(),
+        dam: String::new(),
+        appearances: Vec::new(),
+        past_performances: Vec::new(),
+        workouts: Vec::new(),
+    };
+    let mut seen_lines = HashSet::new();
+    let mut seen_works = HashSet::new();
+
+    for appearance in &appearances {
+        let racecard = read_racecard_by_id(pool, appearance.racecard_id)
+            .await
+            .map_err(|e| format!("Failed to load racecard {}: {}", appearance.racecard_id, e))?;
+        let Some(horse) = racecard
+            .races
+            .into_iter()
+            .flat_map(|race| race.horses)
+            .find(|horse| horse.id == appearance.horse_id)
+        else {
+            continue;
+        };
+
+        if history.horse_name.is_empty() {
+            history.horse_name = horse.horse_name.clone();
+            history.year_of_birth = horse.year_of_birth.map(|y| y as i64);
+            history.sire = horse.sire.clone();
+            history.dam = horse.dam.clone();
+        }
+
+        for pp in horse.past_performances {
+            if seen_lines.insert(line_key(&pp)) {
+                history.past_performances.push(pp);
+            }
+        }
+        for workout in horse.workouts {
+            if seen_works.insert(workout_key(&workout)) {
+                history.workouts.push(workout);
+            }
+        }
+    }
+
+    if history.horse_name.is_empty() {
+        return Err(format!("No stored card has horse {}", horse_key));
+    }
+
+    history.past_performances.sort_by_cached_key(|pp| {
+        std::cmp::Reverse((sort_date(&pp.race_date), pp.race_number.map(|n| n as i64)))
+    });
+    history.workouts.sort_by_cached_key(|workout| std::cmp::Reverse(sort_date(&workout.date)));
+    history.appearances = appearances;
+
+    Ok(history)
+}
+
+// A start is the same start on every card: same date, track and race.
+fn line_key(pp: &PastPerformance) -> (String, String, Option<i64>) {
+    (
+        sort_date(&pp.race_date),
+        pp.track_code.trim().to_uppercase(),
+        pp.race_number.map(|n| n as i64),
+    )
+}
+
+// Works have no race number; the distance separates two works at one track on one day.
+fn workout_key(workout: &Workout) -> (String, String, String) {
+    (
+        sort_date(&workout.date),
+        workout.track.trim().to_uppercase(),
+        workout.distance.map(|d| (d as f64).to_string()).unwrap_or_default(),
+    )
+}
+
+// YYYYMMDD when the date parses, so lines from cards with different date formats still match.
+fn sort_date(value: &str) -> String {
+    pp_date(value).unwrap_or_else(|| value.trim().to_string())
+}
@@ -0,0 +1,392 @@
+use std::collections::{BTreeMap, HashMap};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use tauri::{AppHandle, Emitter, State};
+use crate::files::read_csv_file;
+use crate::sqlite::race_meta::rerank_races;
+use crate::sqlite::results::normalize_program_number;
+
+pub const LIVE_ODDS_EVENT: &str = "live-odds-updated";
+
+// Odds are odds-to-1; leave a field out to keep it as it is.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToteEntry {
+    pub program_number: String,
+    #[serde(default)]
+    pub odds: Option<f64>,
+    #[serde(default)]
+    pub scratched: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ToteTick {
+    pub odds: Option<f64>,
+    pub scratched: Option<bool>,
+    pub source: String,
+    pub recorded_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HorseTote {
+    pub horse_id: i64,
+    pub program_number: String,
+    pub horse_name: String,
+    pub scratched: bool,
+    pub current_odds: Option<f64>,
+    // Oldest first, so the odds movement reads left to right.
+    pub ticks: Vec<ToteTick>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScratchChange {
+    pub race_id: i64,
+    pub horse_id: i64,
+    pub scratched: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LiveOddsEvent {
+    pub racecard_id: i64,
+    pub race_ids: Vec<i64>,
+    pub scratches: Vec<ScratchChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ToteImportSummary {
+    pub races_updated: usize,
+    pub entries_recorded: usize,
+}
+
+#[tauri::command]
+pub async fn set_live_odds(
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+    race_id: i64,
+    entries: Vec<ToteEntry>,
+) -> Result<usize, String> {
+    let (recorded, scratches) = store_tote_entries(&pool, race_id, entries, "manual").await?;
+
+    let racecard_id = sqlx::query_scalar::<_, i64>("SELECT racecard_id FROM races WHERE id = ?;")
+        .bind(race_id)
+        .fetch_one(&*pool)
+        .await
+        .map_err(|e| format!("Failed to look up race {}: {}", race_id, e))?;
+    let scratched_races = scratched_race_ids(&scratches);
+    let _ = app.emit(
+        LIVE_ODDS_EVENT,
+        LiveOddsEvent { racecard_id, race_ids: vec![race_id], scratches },
+    );
+    rerank_races(&app, &pool, &scratched_races).await;
+
+    Ok(recorded)
+}
+
+// CSV with race_number, program_number and odds columns, plus an optional scratched column.
+#[tauri::command]
+pub async fn import_live_odds_file(
+    app: AppHandle,
+    pool: State<'_, SqlitePool>,
+    racecard_id: i64,
+    path: String,
+) -> Result<ToteImportSummary, String> {
+    let (summary, event) = import_live_odds_csv(&pool, racecard_id, &path).await?;
+    let scratched_races = scratched_race_ids(&event.scratches);
+    let _ = app.emit(LIVE_ODDS_EVENT, event);
+    rerank_races(&app, &pool, &scratched_races).await;
+    Ok(summary)
+}
+
+fn scratched_race_ids(scratches: &[ScratchChange]) -> Vec<i64> {
+    let mut race_ids: Vec<i64> = scratches.iter().map(|s| s.race_id).collect();
+    race_ids.sort_unstable();
+    race_ids.dedup();
+    race_ids
+}
+
+#[tauri::command]
+pub async fn get_live_odds(pool: State<'_, SqlitePool>, race_id: i64) -> Result<Vec<HorseTote>, String> {
+    read_race_tote(&pool, race_id).await
+}
+
+pub async fn store_tote_entries(
+    pool: &SqlitePool,
+    race_id: i64,
+    entries: Vec<ToteEntry>,
+    source: &str,
+) -> Result<(usize, Vec<ScratchChange>), String> {
+    let resolved = resolve_tote_entries(pool, race_id, entries).await?;
+
+    let mut tx = pool.begin().await.map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let stored = write_tote_entries(&mut tx, race_id, &resolved, source).await?;
+    tx.commit().await.map_err(|e| format!("Failed to commit odds: {}", e))?;
+
+    Ok(stored)
+}
+
+// Checks the entries against the race's entrants and pairs each with its horse id and current
+// scratch status, writing nothing. Entries that change nothing are dropped.
+async fn resolve_tote_entries(
+    pool: &SqlitePool,
+    race_id: i64,
+    entries: Vec<ToteEntry>,
+) -> Result<Vec<(i64, bool, ToteEntry)>, String> {
+    let entrants = sqlx::query("SELECT id, program_number, scratched FROM horses WHERE race_id = ?;")
+        .bind(race_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to load entrants: {}", e))?;
+
+    let mut resolved = Vec::with_capacity(entries.len());
+    for entry in entries {
+        if let Some(odds) = entry.odds {
+            if !odds.is_finite() || odds < 0.0 {
+                return Err(format!("Invalid odds {} for program number {}", odds, entry.program_number));
+            }
+        }
+        if entry.odds.is_none() && entry.scratched.is_none() {
+            continue;
+        }
+
+        let program_number = normalize_program_number(&entry.program_number);
+        let row = entrants
+            .iter()
+            .find(|row| normalize_program_number(&row.get::<String, _>("program_number")) == program_number)
+            .ok_or_else(|| format!("Program number {} is not an entrant in this race", entry.program_number))?;
+        resolved.push((row.get::<i64, _>("id"), row.get::<bool, _>("scratched"), entry));
+    }
+
+    Ok(resolved)
+}
+
+async fn write_tote_entries(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    race_id: i64,
+    resolved: &[(i64, bool, ToteEntry)],
+    source: &str,
+) -> Result<(usize, Vec<ScratchChange>), String> {
+    let recorded_at = chrono::Utc::now().to_rfc3339();
+    let mut recorded = 0;
+    let mut scratches = Vec::new();
+
+    for (horse_id, was_scratched, entry) in resolved {
+        // Only a change of status is a scratch event.
+        let scratched = entry.scratched.filter(|s| s != was_scratched);
+        if let Some(scratched) = scratched {
+            sqlx::query("UPDATE horses SET scratched = ? WHERE id = ?;")
+                .bind(scratched)
+                .bind(horse_id)
+                .execute(&mut **tx)
+                .await
+                .map_err(|e| format!("Failed to update scratch status: {}", e))?;
+            scratches.push(ScratchChange { race_id, horse_id: *horse_id, scratched });
+        }
+        if entry.odds.is_none() && scratched.is_none() {
+            continue;
+        }
+
+        sqlx::query(
+            "INSERT INTO tote_entries (horse_id, odds, scratched, source, recorded_at) VALUES (?, ?, ?, ?, ?);",
+        )
+        .bind(horse_id)
+        .bind(entry.odds)
+        .bind(scratched)
+        .bind(source)
+        .bind(&recorded_at)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| format!("Failed to store odds: {}", e))?;
+        recorded += 1;
+    }
+
+    Ok((recorded, scratches))
+}
+
+// Keeps the scratch history complete when a horse is scratched from the card itself.
+pub async fn record_scratch(pool: &SqlitePool, horse_id: i64, scratched: bool, source: &str) -> Result<(), String> {
+    sqlx::query("INSERT INTO tote_entries (horse_id, scratched, source, recorded_at) VALUES (?, ?, ?, ?);")
+        .bind(horse_id)
+        .bind(scratched)
+        .bind(source)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to record scratch: {}", e))?;
+
+    Ok(())
+}
+
+// Every race in the file is checked before any is written, and all are written together, so a bad
+// race leaves the card's odds and scratches as they were.
+pub async fn import_live_odds_csv(
+    pool: &SqlitePool,
+    racecard_id: i64,
+    path: &str,
+) -> Result<(ToteImportSummary, LiveOddsEvent), String> {
+    let rows = read_csv_file(path).await?;
+
+    let mut by_race: BTreeMap<i64, Vec<ToteEntry>> = BTreeMap::new();
+    for row in &rows {
+        let line = row.line;
+        let cell = |column: &str| row.get(column).map(|v| v.trim()).filter(|v| !v.is_empty());
+
+        let race_number = cell("race_number")
+            .ok_or_else(|| format!("Line {}: race_number is required", line))?;
+        let race_number = race_number
+            .parse::<i64>()
+            .map_err(|_| format!("Line {}: invalid race_number '{}'", line, race_number))?;
+        let program_number = cell("program_number")
+            .ok_or_else(|| format!("Line {}: program_number is required", line))?
+            .to_string();
+        let odds = match cell("odds") {
+            Some(odds) => Some(parse_odds(odds).ok_or_else(|| format!("Line {}: invalid odds '{}'", line, odds))?),
+            None => None,
+        };
+        let scratched = match cell("scratched").map(|v| v.to_lowercase()) {
+            Some(v) if ["1", "y", "yes", "true", "scr"].contains(&v.as_str()) => Some(true),
+            Some(v) if ["0", "n", "no", "false"].contains(&v.as_str()) => Some(false),
+            Some(v) => return Err(format!("Line {}: invalid scratched '{}'", line, v)),
+            None => None,
+        };
+
+        by_race.entry(race_number).or_default().push(ToteEntry { program_number, odds, scratched });
+    }
+
+    let source = format!(
+        "csv:{}",
+        std::path::Path::new(path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    );
+
+    let mut races = Vec::with_capacity(by_race.len());
+    for (race_number, entries) in by_race {
+        let race_id = sqlx::query_scalar::<_, i64>("SELECT id FROM races WHERE racecard_id = ? AND race_number = ?;")
+            .bind(racecard_id)
+            .bind(race_number)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| format!("Failed to look up race {}: {}", race_number, e))?
+            .ok_or_else(|| format!("Race {} is not on this racecard", race_number))?;
+
+        let resolved = resolve_tote_entries(pool, race_id, entries)
+            .await
+            .map_err(|e| format!("Race {}: {}", race_number, e))?;
+        races.push((race_number, race_id, resolved));
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let mut summary = ToteImportSummary { races_updated: 0, entries_recorded: 0 };
+    let mut event = LiveOddsEvent { racecard_id, race_ids: Vec::new(), scratches: Vec::new() };
+    for (race_number, race_id, resolved) in &races {
+        let (recorded, scratches) = write_tote_entries(&mut tx, *race_id, resolved, &source)
+            .await
+            .map_err(|e| format!("Race {}: {}", race_number, e))?;
+        summary.races_updated += 1;
+        summary.entries_recorded += recorded;
+        event.race_ids.push(*race_id);
+        event.scratches.extend(scratches);
+    }
+    tx.commit().await.map_err(|e| format!("Failed to commit odds: {}", e))?;
+
+    Ok((summary, event))
+}
+
+pub async fn read_race_tote(pool: &SqlitePool, race_id: i64) -> Result<Vec<HorseTote>, String> {
+    let horses = sqlx::query(
+        "SELECT id, program_number, horse_name, scratched FROM horses WHERE race_id = ? ORDER BY id ASC;",
+    )
+    .bind(race_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load entrants: {}", e))?;
+
+    let ticks = sqlx::query(
+        "SELECT t.horse_id, t.odds, t.scratched, t.source, t.recorded_at
+         FROM tote_entries t
+         JOIN horses h ON h.id = t.horse_id
+         WHERE h.race_id = ?
+         ORDER BY t.recorded_at ASC, t.id ASC;",
+    )
+    .bind(race_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to load odds: {}", e))?;
+
+    let mut by_horse: HashMap<i64, Vec<ToteTick>> = HashMap::new();
+    for row in ticks {
+        by_horse.entry(row.get("horse_id")).or_default().push(ToteTick {
+            odds: row.get("odds"),
+            scratched: row.get("scratched"),
+            source: row.get("source"),
+            recorded_at: row.get("recorded_at"),
+        });
+    }
+
+    Ok(horses
+        .into_iter()
+        .map(|row| {
+            let horse_id: i64 = row.get("id");
+            let ticks = by_horse.remove(&horse_id).unwrap_or_default();
+            HorseTote {
+                horse_id,
+                program_number: row.get("program_number"),
+                horse_name: row.get("horse_name"),
+                scratched: row.get("scratched"),
+                current_odds: ticks.iter().rev().find_map(|tick| tick.odds),
+                ticks,
+            }
+        })
+        .collect())
+}
+
+// The latest entered odds per program number, the shape the fair-odds line takes.
+pub async fn current_odds(pool: &SqlitePool, race_id: i64) -> Result<HashMap<String, f64>, String> {
+    Ok(read_race_tote(pool, race_id)
+        .await?
+        .into_iter()
+        .filter_map(|horse| horse.current_odds.map(|odds| (horse.program_number.trim().to_string(), odds)))
+        .collect())
+}
+
+// Accepts "5-2", "5/2", "7", "2.5" and "EVN" (even money).
+pub fn parse_odds(text: &str) -> Option<f64> {
+    let text = text.trim();
+    if text.eq_ignore_ascii_case("evn") || text.eq_ignore_ascii_case("even") {
+        return Some(1.0);
+    }
+
+    let odds = match text.split_once(['-', '/']) {
+        Some((win, stake)) => {
+            let stake = stake.trim().parse::<f64>().ok().filter(|s| *s > 0.0)?;
+            win.trim().parse::<f64>().ok()? / stake
+        }
+        None => text.parse::<f64>().ok()?,
+    };
+    Some(odds).filter(|o| o.is_finite() && *o >= 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_odds;
+
+    #[test]
+    fn parses_tote_formats() {
+        assert_eq!(parse_odds("5-2"), Some(2.5));
+        assert_eq!(parse_odds("5/2"), Some(2.5));
+        assert_eq!(parse_odds(" 7 "), Some(7.0));
+        assert_eq!(parse_odds("2.5"), Some(2.5));
+        assert_eq!(parse_odds("EVN"), Some(1.0));
+        assert_eq!(parse_odds("even"), Some(1.0));
+        assert_eq!(parse_odds("1 - 5"), Some(0.2));
+    }
+
+    #[test]
+    fn rejects_bad_odds() {
+        assert_eq!(parse_odds(""), None);
+        assert_eq!(parse_odds("abc"), None);
+        assert_eq!(parse_odds("5-0"), None);
+        assert_eq!(parse_odds("-3"), None);
+        assert_eq!(parse_odds("inf"), None);
+    }
+}
@@ -0,0 +1,360 @@
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::State;
+use crate::sqlite::racecards::read_racecard_by_id;
+use crate::sqlite::results::read_results_by_racecard_id;
+use crate::states::global_state::global_state;
+
+// A .DRF younger than this may still be on its way through build_racecard.
+const DRF_MIN_AGE: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchiveReport {
+    pub path: String,
+    pub racecards_archived: usize,
+    pub racecards_deleted: usize,
+    pub bytes_written: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanupReport {
+    pub files_removed: Vec<String>,
+    // Still referenced by a stored card, or too new to be sure the import has finished.
+    pub files_skipped: Vec<String>,
+    pub bytes_reclaimed: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OptimizeReport {
+    pub size_before: u64,
+    pub size_after: u64,
+    pub bytes_reclaimed: u64,
+    pub free_pages_before: i64,
+}
+
+#[tauri::command]
+pub async fn delete_racecard(
+    pool: State<'_, SqlitePool>,
+    racecard_id: i64,
+) -> Result<(), String> {
+    delete_racecard_by_id(&pool, racecard_id)
+        .await
+        .map_err(|e| format!("Failed to delete racecard: {}", e))
+}
+
+#[tauri::command]
+pub async fn archive_racecards(
+    pool: State<'_, SqlitePool>,
+    before_date: String,
+    path: String,
+    delete_archived: bool,
+) -> Result<ArchiveReport, String> {
+    archive_racecards_before(&pool, &before_date, &path, delete_archived).await
+}
+
+#[tauri::command]
+pub async fn clean_racecard_files(pool: State<'_, SqlitePool>) -> Result<CleanupReport, String> {
+    remove_orphaned_drf_files(&pool).await
+}
+
+#[tauri::command]
+pub async fn optimize_database(pool: State<'_, SqlitePool>) -> Result<OptimizeReport, String> {
+    vacuum_database(&pool).await
+}
+
+// Not every table that hangs off a card cascades: the ones railbreaker-lib creates (races, horses,
+// past performances, workouts, trainer stats) may not. So every table with a horse_id, race_id or
+// racecard_id column is cleared explicitly, lowest level first (horses is cleared with the race
+// level, races with the card level), all in one transaction so a failure leaves the card whole.
+pub async fn delete_racecard_by_id(pool: &SqlitePool, racecard_id: i64) -> Result<(), sqlx::Error> {
+    let columns: Vec<(String, String)> = sqlx::query_as(
+        "SELECT m.name, p.name FROM sqlite_master m JOIN pragma_table_info(m.name) p \
+         WHERE m.type = 'table' AND m.name NOT LIKE 'sqlite_%' AND m.sql NOT LIKE 'CREATE VIRTUAL%' \
+         AND p.name IN ('horse_id', 'race_id', 'racecard_id') ORDER BY m.name;",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let levels = [
+        ("horse_id", "SELECT h.id FROM horses h JOIN races r ON r.id = h.race_id WHERE r.racecard_id = ?"),
+        ("race_id", "SELECT id FROM races WHERE racecard_id = ?"),
+        ("racecard_id", "SELECT ?"),
+    ];
+
+    let mut tx = pool.begin().await?;
+    let mut cleared: HashSet<&str> = HashSet::new();
+    for (column, ids) in levels {
+        for (table, _) in columns.iter().filter(|(_, c)| c == column) {
+            if !cleared.insert(table) {
+                continue;
+            }
+            sqlx::query(&format!("DELETE FROM \"{}\" WHERE {} IN ({});", table, column, ids))
+                .bind(racecard_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+    sqlx::query("DELETE FROM racecards WHERE id = ?;")
+        .bind(racecard_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+pub async fn archive_racecards_before(
+    pool: &SqlitePool,
+    before_date: &str,
+    path: &str,
+    delete_archived: bool,
+) -> Result<ArchiveReport, String> {
+    let racecard_ids = sqlx::query_scalar::<_, i64>("SELECT id FROM racecards WHERE date < ? ORDER BY date ASC;")
+        .bind(before_date)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to load racecards: {}", e))?;
+
+    let mut entries = Vec::with_capacity(racecard_ids.len());
+    for racecard_id in &racecard_ids {
+        let racecard = read_racecard_by_id(pool, *racecard_id)
+            .await
+            .map_err(|e| format!("Failed to load racecard {}: {}", racecard_id, e))?;
+        let results = read_results_by_racecard_id(pool, *racecard_id)
+            .await
+            .map_err(|e| format!("Failed to load results for racecard {}: {}", racecard_id, e))?;
+
+        let name = format!("{}-{}-{}.json", racecard.date, racecard.track_code, racecard.id);
+        let json = serde_json::to_vec_pretty(&serde_json::json!({
+            "racecard": racecard,
+            "results": results,
+        }))
+        .map_err(|e| format!("Failed to serialize racecard {}: {}", racecard_id, e))?;
+
+        entries.push((name, json));
+    }
+
+    let bytes_written = write_archive(Path::new(path), &entries)?;
+
+    let mut racecards_deleted = 0;
+    if delete_archived {
+        for racecard_id in &racecard_ids {
+            delete_racecard_by_id(pool, *racecard_id)
+                .await
+                .map_err(|e| format!("Failed to delete racecard {}: {}", racecard_id, e))?;
+            racecards_deleted += 1;
+        }
+    }
+
+    Ok(ArchiveReport {
+        path: path.to_string(),
+        racecards_archived: entries.len(),
+        racecards_deleted,
+        bytes_written,
+    })
+}
+
+fn write_archive(path: &Path, entries: &[(String, Vec<u8>)]) -> Result<u64, String> {
+    let file = std::fs::File::create(path)
+        .map_err(|e| format!("Failed to create archive '{}': {}", path.display(), e))?;
+
+    let mut writer = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    for (name, json) in entries {
+        writer
+            .start_file(name.as_str(), options)
+            .map_err(|e| format!("Failed to add {} to archive: {}", name, e))?;
+        writer
+            .write_all(json)
+            .map_err(|e| format!("Failed to write {} to archive: {}", name, e))?;
+    }
+
+    writer
+        .finish()
+        .map_err(|e| format!("Failed to finish archive: {}", e))?;
+
+    std::fs::metadata(path)
+        .map(|m| m.len())
+        .map_err(|e| format!("Failed to read archive size: {}", e))
+}
+
+// Only files no stored card points at are removed. Cards record the zip name rather than the file
+// extracted from it, so a file counts as referenced when its name (less any "-2" suffix from
+// extraction) matches the stem of a card's zip or .DRF name.
+pub async fn remove_orphaned_drf_files(pool: &SqlitePool) -> Result<CleanupReport, String> {
+    let racecards_dir = {
+        let global_state = global_state()
+            .lock()
+            .map_err(|e| format!("Failed to lock global state: {}", e))?;
+        PathBuf::from(format!("{}/Racecards", global_state.current_directory))
+    };
+
+    let referenced: HashSet<String> = sqlx::query_scalar::<_, String>("SELECT zip_file_name FROM racecards;")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to load racecards: {}", e))?
+        .iter()
+        .filter_map(|zip_file_name| file_stem(zip_file_name.rsplit('/').next().unwrap_or(zip_file_name)))
+        .collect();
+
+    let entries = std::fs::read_dir(&racecards_dir)
+        .map_err(|e| format!("Failed to read '{}': {}", racecards_dir.display(), e))?;
+
+    let now = SystemTime::now();
+    let mut report = CleanupReport { files_removed: Vec::new(), files_skipped: Vec::new(), bytes_reclaimed: 0 };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_drf = path
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("drf"))
+            .unwrap_or(false);
+        if !is_drf {
+            continue;
+        }
+
+        let metadata = match entry.metadata() {
+            Ok(metadata) if metadata.is_file() => metadata,
+            _ => continue,
+        };
+        let old_enough = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .map(|age| age >= DRF_MIN_AGE)
+            .unwrap_or(false);
+        if !old_enough || is_referenced(&path, &referenced) {
+            report.files_skipped.push(path.to_string_lossy().to_string());
+            continue;
+        }
+
+        tokio::fs::remove_file(&path)
+            .await
+            .map_err(|e| format!("Failed to remove '{}': {}", path.display(), e))?;
+        report.bytes_reclaimed += metadata.len();
+        report.files_removed.push(path.to_string_lossy().to_string());
+    }
+
+    Ok(report)
+}
+
+fn is_referenced(path: &Path, referenced: &HashSet<String>) -> bool {
+    let Some(stem) = path.file_name().and_then(|name| file_stem(&name.to_string_lossy())) else {
+        return false;
+    };
+    let unsuffixed = stem
+        .rsplit_once('-')
+        .filter(|(_, counter)| !counter.is_empty() && counter.chars().all(|c| c.is_ascii_digit()))
+        .map(|(base, _)| base.to_string());
+
+    referenced.contains(&stem) || unsuffixed.is_some_and(|base| referenced.contains(&base))
+}
+
+fn file_stem(file_name: &str) -> Option<String> {
+    Path::new(file_name)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_lowercase())
+        .filter(|stem| !stem.is_empty())
+}
+
+pub async fn vacuum_database(pool: &SqlitePool) -> Result<OptimizeReport, String> {
+    let database_path = crate::sqlite::get_database_file_path()?;
+
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE);")
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to checkpoint database: {}", e))?;
+
+    let size_before = database_size(&database_path);
+    let free_pages_before = sqlx::query_scalar::<_, i64>("PRAGMA freelist_count;")
+        .fetch_one(pool)
+        .await
+        .map_err(|e| format!("Failed to read free pages: {}", e))?;
+
+    sqlx::query("VACUUM;")
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to vacuum database: {}", e))?;
+
+    sqlx::query("ANALYZE;")
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to analyze database: {}", e))?;
+
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE);")
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to checkpoint database: {}", e))?;
+
+    let size_after = database_size(&database_path);
+
+    Ok(OptimizeReport {
+        size_before,
+        size_after,
+        bytes_reclaimed: size_before.saturating_sub(size_after),
+        free_pages_before,
+    })
+}
+
+fn database_size(database_path: &str) -> u64 {
+    ["", "-wal", "-shm"]
+        .iter()
+        .filter_map(|suffix| std::fs::metadata(format!("{}{}", database_path, suffix)).ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::delete_racecard_by_id;
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+    use sqlx::SqlitePool;
+    use std::str::FromStr;
+
+    async fn count(pool: &SqlitePool, table: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {};", table))
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn deletes_every_row_of_a_card() {
+        let opts = SqliteConnectOptions::from_str("sqlite::memory:").unwrap().foreign_keys(true);
+        let pool = SqlitePoolOptions::new().max_connections(1).connect_with(opts).await.unwrap();
+
+        // Library tables as railbreaker-lib may create them: references without cascades.
+        for statement in [
+            "CREATE TABLE racecards (id INTEGER PRIMARY KEY, date TEXT);",
+            "CREATE TABLE races (id INTEGER PRIMARY KEY, racecard_id INTEGER NOT NULL REFERENCES racecards(id));",
+            "CREATE TABLE horses (id INTEGER PRIMARY KEY, race_id INTEGER NOT NULL REFERENCES races(id));",
+            "CREATE TABLE past_performances (id INTEGER PRIMARY KEY, horse_id INTEGER NOT NULL REFERENCES horses(id));",
+            "CREATE TABLE workouts (id INTEGER PRIMARY KEY, horse_id INTEGER NOT NULL REFERENCES horses(id));",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+
+        for card in [1, 2] {
+            sqlx::query("INSERT INTO racecards (id, date) VALUES (?, '20250101');").bind(card).execute(&pool).await.unwrap();
+            sqlx::query("INSERT INTO races (id, racecard_id) VALUES (?, ?);").bind(card).bind(card).execute(&pool).await.unwrap();
+            sqlx::query("INSERT INTO horses (id, race_id) VALUES (?, ?);").bind(card).bind(card).execute(&pool).await.unwrap();
+            sqlx::query("INSERT INTO past_performances (horse_id) VALUES (?);").bind(card).execute(&pool).await.unwrap();
+            sqlx::query("INSERT INTO workouts (horse_id) VALUES (?);").bind(card).execute(&pool).await.unwrap();
+            sqlx::query("INSERT INTO horse_results (race_id, horse_id) VALUES (?, ?);").bind(card).bind(card).execute(&pool).await.unwrap();
+        }
+
+        delete_racecard_by_id(&pool, 1).await.unwrap();
+
+        // Only the other card's rows are left.
+        for table in ["racecards", "races", "horses", "past_performances", "workouts", "horse_results"] {
+            assert_eq!(count(&pool, table).await, 1, "{}", table);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use sqlx::migrate::Migrator;
+use sqlx::SqlitePool;
+
+// Ordered .sql files from src-tauri/migrations, embedded at compile time. Applied versions are
+// recorded in the _sqlx_migrations table.
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+pub async fn schema_version(pool: &SqlitePool) -> Result<i64, sqlx::Error> {
+    Ok(applied_versions(pool).await?.into_iter().max().unwrap_or(0))
+}
+
+// Brings the database up to the latest schema. If anything is pending on a database that already
+// had tables before this startup, a copy is taken first so a failed migration never costs the user
+// their racecards. The caller checks that before creating the racecard tables.
+pub async fn run_migrations(pool: &SqlitePool, database_path: &str, existing_database: bool) -> Result<i64, String> {
+    let applied = applied_versions(pool)
+        .await
+        .map_err(|e| format!("Failed to read schema version: {}", e))?;
+
+    let pending: Vec<i64> = MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration() && !applied.contains(&m.version))
+        .map(|m| m.version)
+        .collect();
+
+    if pending.is_empty() {
+        return Ok(applied.into_iter().max().unwrap_or(0));
+    }
+
+    let backup_path = if existing_database {
+        let current_version = applied.iter().copied().max().unwrap_or(0);
+        Some(backup_database(pool, database_path, current_version).await?)
+    } else {
+        None
+    };
+
+    if let Err(e) = MIGRATOR.run(pool).await {
+        let backup_note = backup_path
+            .map(|path| format!(" A backup of the database was saved to {}.", path.display()))
+            .unwrap_or_default();
+        return Err(format!(
+            "Failed to migrate database to schema version {}: {}.{}",
+            pending.iter().max().copied().unwrap_or(0),
+            e,
+            backup_note
+        ));
+    }
+
+    schema_version(pool)
+        .await
+        .map_err(|e| format!("Failed to read schema version: {}", e))
+}
+
+async fn applied_versions(pool: &SqlitePool) -> Result<HashSet<i64>, sqlx::Error> {
+    let table_exists = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations';",
+    )
+    .fetch_one(pool)
+    .await?
+        > 0;
+
+    if !table_exists {
+        return Ok(HashSet::new());
+    }
+
+    let versions = sqlx::query_scalar::<_, i64>("SELECT version FROM _sqlx_migrations WHERE success = 1;")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(versions.into_iter().collect())
+}
+
+pub async fn has_tables(pool: &SqlitePool) -> Result<bool, String> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%';",
+    )
+    .fetch_one(pool)
+    .await
+    .map(|count| count > 0)
+    .map_err(|e| format!("Failed to inspect database: {}", e))
+}
+
+// VACUUM INTO writes a consistent copy even with the WAL still open, unlike copying the file.
+async fn backup_database(pool: &SqlitePool, database_path: &str, version: i64) -> Result<PathBuf, String> {
+    let backups_dir = Path::new(database_path)
+        .parent()
+        .map(|dir| dir.join("Backups"))
+        .ok_or_else(|| format!("Invalid database path: {}", database_path))?;
+
+    std::fs::create_dir_all(&backups_dir)
+        .map_err(|e| format!("Failed to create backup directory: {}", e))?;
+
+    let backup_path = backups_dir.join(format!(
+        "railbreaker-v{}-{}.db",
+        version,
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+
+    sqlx::query("VACUUM INTO ?;")
+        .bind(backup_path.to_string_lossy().to_string())
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to back up database before migrating: {}", e))?;
+
+    Ok(backup_path)
+}
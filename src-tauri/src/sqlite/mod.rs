@@ -1,7 +1,20 @@
+pub mod auto_import;
+pub mod bet_tickets;
 pub mod db;
+pub mod horse_history;
+pub mod live_odds;
+pub mod maintenance;
+pub mod migrations;
+pub mod pp_lines;
+pub mod race_meta;
 pub mod racecards;
+pub mod results;
+pub mod search;
+pub mod watch_list;
 
 use crate::global_state;
+use railbreaker_lib::sqlite::racecards::create_tables;
+use sqlx::SqlitePool;
 
 pub fn get_database_file_path() -> Result<String, String> {
     let global_state = global_state()
@@ -13,4 +26,35 @@ pub fn get_database_file_path() -> Result<String, String> {
     }
 
     Ok(format!("{}/Racecards/railbreaker.db", global_state.current_directory))
-}
\ No newline at end of file
+}
+
+pub async fn init_database(database_url: &str) -> anyhow::Result<SqlitePool> {
+    let pool = db::make_pool(database_url).await?;
+    prepare_database(&pool, database_url)
+        .await
+        .map_err(anyhow::Error::msg)?;
+
+    Ok(pool)
+}
+
+// The racecard tables come from railbreaker-lib; everything the app adds on top of them is a
+// versioned migration. Returns the schema version the database ends up at.
+pub async fn prepare_database(pool: &SqlitePool, database_path: &str) -> Result<i64, String> {
+    let existing_database = migrations::has_tables(pool).await?;
+
+    create_tables(pool)
+        .await
+        .map_err(|e| format!("Failed to create racecard tables: {}", e))?;
+
+    let version = migrations::run_migrations(pool, database_path, existing_database).await?;
+
+    // The search index and PP lines can always be rebuilt, so failures here are logged rather than fatal.
+    if let Err(e) = search::index_missing_racecards(pool).await {
+        eprintln!("{}", e);
+    }
+    if let Err(e) = pp_lines::index_missing_pp_lines(pool).await {
+        eprintln!("{}", e);
+    }
+
+    Ok(version)
+}
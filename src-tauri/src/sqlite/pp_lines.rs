@@ -0,0 +1,338 @@
+use railbreaker_lib::models::racecard::{Horse, PastPerformance, Race, Racecard};
+use sqlx::{QueryBuilder, Row, Sqlite, SqliteConnection, SqlitePool};
+use crate::analysis::distance_furlongs;
+use crate::sqlite::racecards::read_racecard_by_id;
+use crate::sqlite::results::read_results_by_racecard_id;
+use crate::sqlite::watch_list::horse_key;
+
+// Brisnet medication codes 4 and 5 mark first-time Lasix.
+const FIRST_LASIX_CODES: [i64; 2] = [4, 5];
+const BLINKERS_ON: &str = "Blinkers on";
+const BLINKERS_OFF: &str = "Blinkers off";
+const EQUIPMENT_CHANGED: &str = "Other change";
+
+// A line keeps the newest non-empty value of each column, so a result entered for today's race and
+// the same race seen later as a past performance fill each other in.
+const UPSERT_LINE: &str = "INSERT INTO pp_lines
+    (track_code, race_date, race_number, horse_key, surface, distance_furlongs, track_condition,
+     entrants, post_position, first_call_position, finish_position, trainer, jockey, race_type,
+     days_since_last_race, first_lasix, equipment_change, odds, win_payoff)
+ VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+ ON CONFLICT(track_code, race_date, race_number, horse_key) DO UPDATE SET
+    surface = CASE WHEN excluded.surface <> '' THEN excluded.surface ELSE pp_lines.surface END,
+    distance_furlongs = COALESCE(excluded.distance_furlongs, pp_lines.distance_furlongs),
+    track_condition = CASE WHEN excluded.track_condition <> '' THEN excluded.track_condition ELSE pp_lines.track_condition END,
+    entrants = COALESCE(excluded.entrants, pp_lines.entrants),
+    post_position = COALESCE(excluded.post_position, pp_lines.post_position),
+    first_call_position = COALESCE(excluded.first_call_position, pp_lines.first_call_position),
+    finish_position = COALESCE(excluded.finish_position, pp_lines.finish_position),
+    trainer = CASE WHEN excluded.trainer <> '' THEN excluded.trainer ELSE pp_lines.trainer END,
+    jockey = CASE WHEN excluded.jockey <> '' THEN excluded.jockey ELSE pp_lines.jockey END,
+    race_type = CASE WHEN excluded.race_type <> '' THEN excluded.race_type ELSE pp_lines.race_type END,
+    days_since_last_race = COALESCE(excluded.days_since_last_race, pp_lines.days_since_last_race),
+    first_lasix = MAX(excluded.first_lasix, pp_lines.first_lasix),
+    equipment_change = CASE WHEN excluded.equipment_change <> '' THEN excluded.equipment_change ELSE pp_lines.equipment_change END,
+    odds = COALESCE(excluded.odds, pp_lines.odds),
+    win_payoff = COALESCE(excluded.win_payoff, pp_lines.win_payoff);";
+
+#[derive(Debug, Clone, Default)]
+pub struct PpLine {
+    pub track_code: String,
+    pub race_date: String,
+    pub race_number: i64,
+    pub horse_key: String,
+    pub surface: String,
+    pub distance_furlongs: Option<f64>,
+    pub track_condition: String,
+    pub entrants: Option<i64>,
+    pub post_position: Option<i64>,
+    pub first_call_position: Option<i64>,
+    pub finish_position: Option<i64>,
+    pub trainer: String,
+    pub jockey: String,
+    pub race_type: String,
+    pub days_since_last_race: Option<i64>,
+    pub first_lasix: bool,
+    pub equipment_change: String,
+    pub odds: Option<f64>,
+    pub win_payoff: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PpLineFilter<'a> {
+    pub track_code: Option<&'a str>,
+    pub surface: Option<&'a str>,
+    pub condition: Option<&'a str>,
+    // YYYYMMDD, the same format as racecards.date; both ends inclusive.
+    pub date_from: Option<&'a str>,
+    pub date_to: Option<&'a str>,
+    pub trainer: Option<&'a str>,
+    pub jockey: Option<&'a str>,
+}
+
+pub async fn index_pp_lines(pool: &SqlitePool, racecard: &Racecard) -> Result<(), sqlx::Error> {
+    let results = result_lines(pool, racecard).await?;
+    let mut tx = pool.begin().await?;
+
+    for horse in racecard.races.iter().flat_map(|race| race.horses.iter()) {
+        let key = horse_key(horse);
+
+        // Newest first, so the following line is the start before.
+        let pps = &horse.past_performances;
+        for (idx, pp) in pps.iter().enumerate() {
+            if let Some(line) = past_performance_line(&key, pp, pps.get(idx + 1)) {
+                upsert_line(&mut tx, &line).await?;
+            }
+        }
+    }
+
+    for line in &results {
+        upsert_line(&mut tx, line).await?;
+    }
+
+    sqlx::query("INSERT OR IGNORE INTO pp_line_sources (racecard_id) VALUES (?);")
+        .bind(racecard.id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await
+}
+
+// Records the starts on a card that have results, so connection statistics pick up a card's
+// outcomes as soon as they are entered.
+pub async fn index_racecard_results(pool: &SqlitePool, racecard_id: i64) -> Result<(), sqlx::Error> {
+    let racecard = read_racecard_by_id(pool, racecard_id).await?;
+    let results = result_lines(pool, &racecard).await?;
+
+    let mut tx = pool.begin().await?;
+    for line in &results {
+        upsert_line(&mut tx, line).await?;
+    }
+    tx.commit().await
+}
+
+async fn result_lines(pool: &SqlitePool, racecard: &Racecard) -> Result<Vec<PpLine>, sqlx::Error> {
+    let results = read_results_by_racecard_id(pool, racecard.id).await?;
+    let mut lines = Vec::new();
+
+    for race in &racecard.races {
+        let starters = race.horses.iter().filter(|h| !h.scratched).count() as i64;
+
+        for horse in race.horses.iter().filter(|h| !h.scratched) {
+            if let Some(result) = results.iter().find(|r| r.horse_id == horse.id) {
+                let mut line = todays_line(racecard, race, horse);
+                line.entrants = Some(starters);
+                line.finish_position = result.finish_position;
+                line.odds = result.final_odds;
+                line.win_payoff = result.win_payoff;
+                lines.push(line);
+            }
+        }
+    }
+
+    Ok(lines)
+}
+
+fn past_performance_line(key: &str, pp: &PastPerformance, previous: Option<&PastPerformance>) -> Option<PpLine> {
+    let race_date = pp_date(&pp.race_date)?;
+    let race_number = pp.race_number.map(|n| n as i64)?;
+    if pp.track_code.trim().is_empty() {
+        return None;
+    }
+
+    Some(PpLine {
+        track_code: pp.track_code.trim().to_uppercase(),
+        race_date,
+        race_number,
+        horse_key: key.to_string(),
+        surface: pp.surface.trim().to_string(),
+        distance_furlongs: distance_furlongs(pp.distance.map(|d| d as f64)),
+        track_condition: pp.track_condition.trim().to_uppercase(),
+        entrants: pp.entrants.map(|n| n as i64),
+        post_position: pp.post_position.map(|n| n as i64),
+        first_call_position: call_position(&pp.first_call_position),
+        finish_position: call_position(&pp.finish_position),
+        trainer: normalize_connection(&pp.trainer),
+        jockey: normalize_connection(&pp.jockey),
+        race_type: pp.race_type.trim().to_uppercase(),
+        days_since_last_race: pp.days_since_last_race.map(|d| d as i64),
+        first_lasix: pp.medication.is_some_and(|m| FIRST_LASIX_CODES.contains(&(m as i64))),
+        equipment_change: previous
+            .map(|previous| equipment_change(&previous.equipment, &pp.equipment))
+            .unwrap_or_default(),
+        odds: pp.odds.map(|o| o as f64),
+        win_payoff: None,
+    })
+}
+
+fn todays_line(racecard: &Racecard, race: &Race, horse: &Horse) -> PpLine {
+    let equipment_change = match horse.equipment_change.map(|e| e as i64) {
+        Some(1) => BLINKERS_ON,
+        Some(2) => BLINKERS_OFF,
+        _ => "",
+    };
+
+    PpLine {
+        track_code: racecard.track_code.trim().to_uppercase(),
+        race_date: racecard.date.clone(),
+        race_number: race.race_number.map(|n| n as i64).unwrap_or_default(),
+        horse_key: horse_key(horse),
+        surface: race.surface.trim().to_string(),
+        distance_furlongs: distance_furlongs(race.distance.map(|d| d as f64)),
+        post_position: horse.post_position.map(|p| p as i64),
+        trainer: normalize_connection(&horse.todays_trainer),
+        jockey: normalize_connection(&horse.todays_jockey),
+        race_type: race.race_type.trim().to_uppercase(),
+        days_since_last_race: horse.days_since_last_race.map(|d| d as i64),
+        first_lasix: horse
+            .todays_medication_new
+            .is_some_and(|m| FIRST_LASIX_CODES.contains(&(m as i64))),
+        equipment_change: equipment_change.to_string(),
+        ..PpLine::default()
+    }
+}
+
+// PP equipment is a short code per start; blinkers are the change worth naming.
+fn equipment_change(previous: &str, current: &str) -> String {
+    let (previous, current) = (previous.trim().to_lowercase(), current.trim().to_lowercase());
+    let change = match (previous.contains('b'), current.contains('b')) {
+        (false, true) => BLINKERS_ON,
+        (true, false) => BLINKERS_OFF,
+        _ if previous != current => EQUIPMENT_CHANGED,
+        _ => "",
+    };
+    change.to_string()
+}
+
+async fn upsert_line(conn: &mut SqliteConnection, line: &PpLine) -> Result<(), sqlx::Error> {
+    sqlx::query(UPSERT_LINE)
+        .bind(&line.track_code)
+        .bind(&line.race_date)
+        .bind(line.race_number)
+        .bind(&line.horse_key)
+        .bind(&line.surface)
+        .bind(line.distance_furlongs)
+        .bind(&line.track_condition)
+        .bind(line.entrants)
+        .bind(line.post_position)
+        .bind(line.first_call_position)
+        .bind(line.finish_position)
+        .bind(&line.trainer)
+        .bind(&line.jockey)
+        .bind(&line.race_type)
+        .bind(line.days_since_last_race)
+        .bind(line.first_lasix)
+        .bind(&line.equipment_change)
+        .bind(line.odds)
+        .bind(line.win_payoff)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn index_missing_pp_lines(pool: &SqlitePool) -> Result<usize, String> {
+    let racecard_ids = sqlx::query_scalar::<_, i64>(
+        "SELECT id FROM racecards WHERE id NOT IN (SELECT racecard_id FROM pp_line_sources);",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to find racecards without past performance lines: {}", e))?;
+
+    for racecard_id in &racecard_ids {
+        let racecard = read_racecard_by_id(pool, *racecard_id)
+            .await
+            .map_err(|e| format!("Failed to load racecard {}: {}", racecard_id, e))?;
+        index_pp_lines(pool, &racecard)
+            .await
+            .map_err(|e| format!("Failed to collect past performances for racecard {}: {}", racecard_id, e))?;
+    }
+
+    Ok(racecard_ids.len())
+}
+
+pub async fn read_pp_lines(pool: &SqlitePool, filter: &PpLineFilter<'_>) -> Result<Vec<PpLine>, sqlx::Error> {
+    let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new("SELECT * FROM pp_lines WHERE 1 = 1");
+
+    if let Some(track_code) = filter.track_code {
+        builder.push(" AND track_code = ").push_bind(track_code.trim().to_uppercase());
+    }
+    if let Some(surface) = filter.surface {
+        builder.push(" AND surface = ").push_bind(surface.trim().to_string());
+    }
+    if let Some(condition) = filter.condition {
+        builder.push(" AND track_condition = ").push_bind(condition.trim().to_uppercase());
+    }
+    if let Some(date_from) = filter.date_from {
+        builder.push(" AND race_date >= ").push_bind(date_from.to_string());
+    }
+    if let Some(date_to) = filter.date_to {
+        builder.push(" AND race_date <= ").push_bind(date_to.to_string());
+    }
+    if let Some(trainer) = filter.trainer {
+        builder.push(" AND trainer = ").push_bind(normalize_connection(trainer));
+    }
+    if let Some(jockey) = filter.jockey {
+        builder.push(" AND jockey = ").push_bind(normalize_connection(jockey));
+    }
+
+    let rows = builder.build().fetch_all(pool).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| PpLine {
+            track_code: row.get("track_code"),
+            race_date: row.get("race_date"),
+            race_number: row.get("race_number"),
+            horse_key: row.get("horse_key"),
+            surface: row.get("surface"),
+            distance_furlongs: row.get("distance_furlongs"),
+            track_condition: row.get("track_condition"),
+            entrants: row.get("entrants"),
+            post_position: row.get("post_position"),
+            first_call_position: row.get("first_call_position"),
+            finish_position: row.get("finish_position"),
+            trainer: row.get("trainer"),
+            jockey: row.get("jockey"),
+            race_type: row.get("race_type"),
+            days_since_last_race: row.get("days_since_last_race"),
+            first_lasix: row.get("first_lasix"),
+            equipment_change: row.get("equipment_change"),
+            odds: row.get("odds"),
+            win_payoff: row.get("win_payoff"),
+        })
+        .collect())
+}
+
+// Names are matched without case (the columns are NOCASE) and with single spaces.
+pub fn normalize_connection(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// PP dates are MM/DD/YYYY (occasionally a two digit year); stored as YYYYMMDD to sort and compare
+// with racecard dates.
+pub fn pp_date(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.len() == 8 && value.chars().all(|c| c.is_ascii_digit()) {
+        return Some(value.to_string());
+    }
+
+    let parts: Vec<&str> = value.split('/').collect();
+    let [month, day, year] = parts.as_slice() else {
+        return None;
+    };
+    let year: i32 = match year.len() {
+        2 => 2000 + year.parse::<i32>().ok()?,
+        4 => year.parse().ok()?,
+        _ => return None,
+    };
+
+    chrono::NaiveDate::from_ymd_opt(year, month.parse().ok()?, day.parse().ok()?)
+        .map(|date| date.format("%Y%m%d").to_string())
+}
+
+// Call positions can carry trailing markers; only the leading number matters here.
+fn call_position(value: &str) -> Option<i64> {
+    let digits: String = value.trim().chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse::<i64>().ok().filter(|position| *position > 0)
+}
@@ -0,0 +1,57 @@
+use railbreaker_lib::contextual_speed_and_pace_model::RaceMeta;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Emitter};
+use crate::analysis::model_parameters::rank_race_with_parameters;
+use crate::commands::config_file_commands::load_model_parameters;
+use crate::sqlite::racecards::read_racecard_by_id;
+
+pub const RACE_META_EVENT: &str = "race-meta-updated";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RaceMetaUpdate {
+    pub racecard_id: i64,
+    pub race_id: i64,
+    pub race_number: Option<i64>,
+    pub meta: RaceMeta,
+}
+
+// Reranks the race from the stored card, so scratches saved a moment ago are taken into account.
+// Nothing is stored: the analysis depends on the model parameters, so a saved copy would go stale.
+pub async fn rerank_race(pool: &SqlitePool, race_id: i64) -> Result<RaceMetaUpdate, String> {
+    let racecard_id = sqlx::query_scalar::<_, i64>("SELECT racecard_id FROM races WHERE id = ?;")
+        .bind(race_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| format!("Failed to look up race {}: {}", race_id, e))?;
+    let racecard = read_racecard_by_id(pool, racecard_id)
+        .await
+        .map_err(|e| format!("Failed to load racecard: {}", e))?;
+    let race = racecard
+        .races
+        .iter()
+        .find(|race| race.id == race_id)
+        .ok_or_else(|| format!("Race {} is not on racecard {}", race_id, racecard_id))?;
+
+    let parameters = load_model_parameters().await;
+    let meta = rank_race_with_parameters(race, Some(racecard.date.as_str()), &parameters);
+
+    Ok(RaceMetaUpdate {
+        racecard_id,
+        race_id,
+        race_number: race.race_number.map(|n| n as i64),
+        meta,
+    })
+}
+
+// The scratch is already saved, so a failed rerank is logged rather than returned.
+pub async fn rerank_races(app: &AppHandle, pool: &SqlitePool, race_ids: &[i64]) {
+    for race_id in race_ids {
+        match rerank_race(pool, *race_id).await {
+            Ok(update) => {
+                let _ = app.emit(RACE_META_EVENT, update);
+            }
+            Err(e) => eprintln!("Failed to rerank race {}: {}", race_id, e),
+        }
+    }
+}
@@ -1,17 +1,27 @@
 use sqlx::{Row, SqlitePool};
-use tauri::State;
+use tauri::{AppHandle, State};
 use railbreaker_lib::models::racecard::Racecard;
 use railbreaker_lib::sqlite::racecards::read_racecard;
 use serde_json::Value;
+use crate::sqlite::live_odds::record_scratch;
+use crate::sqlite::race_meta::rerank_races;
+use crate::sqlite::watch_list::{notify_watched_horses, sync_watch_note};
+use crate::tracks::resolve_track_names;
 
 #[tauri::command]
 pub async fn add_racecard(
+    app: AppHandle,
     pool: State<'_, SqlitePool>,
     racecard: Racecard,
 ) -> Result<Racecard, String> {
-    railbreaker_lib::sqlite::racecards::add_racecard(&pool, racecard)
+    let mut racecard = railbreaker_lib::sqlite::racecards::add_racecard(&pool, racecard)
         .await
-        .map_err(|e| format!("Failed to add racecard: {}", e))
+        .map_err(|e| format!("Failed to add racecard: {}", e))?;
+
+    let watched = crate::import::after_racecard_added(&pool, &mut racecard).await;
+    notify_watched_horses(&app, &racecard, watched);
+
+    Ok(racecard)
 }
 
 #[tauri::command]
@@ -19,16 +29,9 @@ pub async fn racecard_exists_by_zip_name(
     pool: State<'_, SqlitePool>,
     zip_file_name: String,
 ) -> Result<bool, String> {
-    let exists = sqlx::query_scalar::<_, i64>(
-        "SELECT 1 FROM racecards WHERE zip_file_name = ? LIMIT 1;",
-    )
-    .bind(zip_file_name)
-    .fetch_optional(&*pool)
-    .await
-    .map_err(|e| format!("Failed to check racecard: {}", e))?
-    .is_some();
-
-    Ok(exists)
+    racecard_exists(&pool, &zip_file_name)
+        .await
+        .map_err(|e| format!("Failed to check racecard: {}", e))
 }
 
 #[tauri::command]
@@ -39,8 +42,9 @@ pub async fn get_racecard_by_id(
     let racecard = read_racecard_by_id(&pool, racecard_id)
         .await
         .map_err(|e| format!("Failed to load racecard: {}", e))?;
-    let value = serde_json::to_value(&racecard)
+    let mut value = serde_json::to_value(&racecard)
         .map_err(|e| format!("Failed to serialize racecard: {}", e))?;
+    resolve_track_names(&mut value);
     Ok(value)
 }
 
@@ -78,21 +82,40 @@ pub async fn update_note(
     note: String,
 ) -> Result<(), String> {
     sqlx::query("UPDATE horses SET note = ? WHERE id = ?;")
-        .bind(note)
+        .bind(&note)
         .bind(horse_id)
         .execute(&*pool)
         .await
         .map_err(|e| format!("Failed to update note: {}", e))?;
 
+    sync_watch_note(&pool, horse_id, &note)
+        .await
+        .map_err(|e| format!("Failed to update watch list note: {}", e))?;
+
     Ok(())
 }
 
+// The race is reranked here and the new analysis arrives as a race-meta-updated event.
 #[tauri::command]
 pub async fn set_scratch(
+    app: AppHandle,
     pool: State<'_, SqlitePool>,
     horse_id: i64,
     scratched: bool,
 ) -> Result<(), String> {
+    let (race_id, was_scratched) =
+        sqlx::query_as::<_, (i64, bool)>("SELECT race_id, scratched FROM horses WHERE id = ?;")
+            .bind(horse_id)
+            .fetch_optional(&*pool)
+            .await
+            .map_err(|e| format!("Failed to look up horse {}: {}", horse_id, e))?
+            .ok_or_else(|| format!("Horse {} does not exist", horse_id))?;
+
+    // Setting the status a horse already has is not a scratch event.
+    if scratched == was_scratched {
+        return Ok(());
+    }
+
     sqlx::query("UPDATE horses SET scratched = ? WHERE id = ?;")
         .bind(scratched)
         .bind(horse_id)
@@ -100,6 +123,11 @@ pub async fn set_scratch(
         .await
         .map_err(|e| format!("Failed to update scratch status: {}", e))?;
 
+    if let Err(e) = record_scratch(&pool, horse_id, scratched, "manual").await {
+        eprintln!("{}", e);
+    }
+    rerank_races(&app, &pool, &[race_id]).await;
+
     Ok(())
 }
 
@@ -111,3 +139,15 @@ pub async fn read_racecard_by_id(pool: &SqlitePool, racecard_id: i64) -> Result<
 
     read_racecard(pool, racecard_row).await
 }
+
+pub async fn racecard_exists(pool: &SqlitePool, zip_file_name: &str) -> Result<bool, sqlx::Error> {
+    let exists = sqlx::query_scalar::<_, i64>(
+        "SELECT 1 FROM racecards WHERE zip_file_name = ? LIMIT 1;",
+    )
+    .bind(zip_file_name)
+    .fetch_optional(pool)
+    .await?
+    .is_some();
+
+    Ok(exists)
+}
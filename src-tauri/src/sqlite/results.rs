@@ -0,0 +1,340 @@
+use std::collections::{BTreeMap, HashSet};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use tauri::State;
+use crate::files::{read_csv_file, CsvRow};
+use crate::sqlite::pp_lines::index_racecard_results;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RaceResultEntry {
+    pub program_number: String,
+    pub finish_position: Option<i64>,
+    pub margin: Option<f64>,
+    pub final_odds: Option<f64>,
+    pub win_payoff: Option<f64>,
+    pub place_payoff: Option<f64>,
+    pub show_payoff: Option<f64>,
+    #[serde(default)]
+    pub claimed: bool,
+    #[serde(default)]
+    pub claimed_by: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HorseResult {
+    pub race_id: i64,
+    pub race_number: i64,
+    pub horse_id: i64,
+    pub program_number: String,
+    pub horse_name: String,
+    pub finish_position: Option<i64>,
+    pub margin: Option<f64>,
+    pub final_odds: Option<f64>,
+    pub win_payoff: Option<f64>,
+    pub place_payoff: Option<f64>,
+    pub show_payoff: Option<f64>,
+    pub claimed: bool,
+    pub claimed_by: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResultsImportSummary {
+    pub races_updated: usize,
+    pub entries_recorded: usize,
+}
+
+#[tauri::command]
+pub async fn set_race_result(
+    pool: State<'_, SqlitePool>,
+    race_id: i64,
+    entries: Vec<RaceResultEntry>,
+) -> Result<usize, String> {
+    let recorded = store_race_result(&pool, race_id, entries, "manual").await?;
+
+    let racecard_id = sqlx::query_scalar::<_, i64>("SELECT racecard_id FROM races WHERE id = ?;")
+        .bind(race_id)
+        .fetch_one(&*pool)
+        .await
+        .map_err(|e| format!("Failed to look up race {}: {}", race_id, e))?;
+    record_connection_results(&pool, racecard_id).await;
+
+    Ok(recorded)
+}
+
+#[tauri::command]
+pub async fn import_results_file(
+    pool: State<'_, SqlitePool>,
+    racecard_id: i64,
+    path: String,
+) -> Result<ResultsImportSummary, String> {
+    let summary = import_results_csv(&pool, racecard_id, &path).await?;
+    record_connection_results(&pool, racecard_id).await;
+    Ok(summary)
+}
+
+// The results are already stored, so a failure here is reported rather than returned.
+async fn record_connection_results(pool: &SqlitePool, racecard_id: i64) {
+    if let Err(e) = index_racecard_results(pool, racecard_id).await {
+        eprintln!("Failed to record results for racecard {} in connection statistics: {}", racecard_id, e);
+    }
+}
+
+#[tauri::command]
+pub async fn get_race_results(
+    pool: State<'_, SqlitePool>,
+    racecard_id: i64,
+) -> Result<Vec<HorseResult>, String> {
+    read_results_by_racecard_id(&pool, racecard_id)
+        .await
+        .map_err(|e| format!("Failed to load race results: {}", e))
+}
+
+pub async fn store_race_result(
+    pool: &SqlitePool,
+    race_id: i64,
+    entries: Vec<RaceResultEntry>,
+    source: &str,
+) -> Result<usize, String> {
+    let resolved = resolve_race_result(pool, race_id, entries).await?;
+
+    let mut tx = pool.begin().await.map_err(|e| format!("Failed to start transaction: {}", e))?;
+    write_race_result(&mut tx, race_id, &resolved, source).await?;
+    tx.commit().await.map_err(|e| format!("Failed to commit results: {}", e))?;
+
+    Ok(resolved.len())
+}
+
+// Checks a race's entries against its entrants and pairs each with its horse id, writing nothing.
+async fn resolve_race_result(
+    pool: &SqlitePool,
+    race_id: i64,
+    entries: Vec<RaceResultEntry>,
+) -> Result<Vec<(i64, RaceResultEntry)>, String> {
+    let entrants = sqlx::query("SELECT id, program_number, scratched FROM horses WHERE race_id = ?;")
+        .bind(race_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to load entrants: {}", e))?;
+
+    if entrants.is_empty() {
+        return Err(format!("Race {} has no entrants", race_id));
+    }
+
+    let starters = entrants
+        .iter()
+        .filter(|row| !row.get::<bool, _>("scratched"))
+        .count() as i64;
+
+    let mut seen = HashSet::new();
+    let mut positions = HashSet::new();
+    let mut resolved = Vec::with_capacity(entries.len());
+
+    for entry in entries {
+        let program_number = normalize_program_number(&entry.program_number);
+        if !seen.insert(program_number.clone()) {
+            return Err(format!("Program number {} is listed more than once", entry.program_number));
+        }
+
+        let row = entrants
+            .iter()
+            .find(|row| normalize_program_number(&row.get::<String, _>("program_number")) == program_number)
+            .ok_or_else(|| format!("Program number {} is not an entrant in this race", entry.program_number))?;
+
+        let scratched: bool = row.get("scratched");
+        if let Some(position) = entry.finish_position {
+            if scratched {
+                return Err(format!(
+                    "Program number {} is scratched and cannot have a finish position",
+                    entry.program_number
+                ));
+            }
+            if position < 1 || position > starters {
+                return Err(format!(
+                    "Finish position {} for program number {} is outside 1-{}",
+                    position, entry.program_number, starters
+                ));
+            }
+            if !positions.insert(position) {
+                return Err(format!("Finish position {} is given to more than one horse", position));
+            }
+        }
+
+        resolved.push((row.get::<i64, _>("id"), entry));
+    }
+
+    Ok(resolved)
+}
+
+async fn write_race_result(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    race_id: i64,
+    resolved: &[(i64, RaceResultEntry)],
+    source: &str,
+) -> Result<(), String> {
+    sqlx::query("DELETE FROM horse_results WHERE race_id = ?;")
+        .bind(race_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| format!("Failed to clear previous results: {}", e))?;
+
+    for (horse_id, entry) in resolved {
+        sqlx::query(
+            "INSERT INTO horse_results (
+                race_id, horse_id, finish_position, margin, final_odds,
+                win_payoff, place_payoff, show_payoff, claimed, claimed_by
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?);",
+        )
+        .bind(race_id)
+        .bind(horse_id)
+        .bind(entry.finish_position)
+        .bind(entry.margin)
+        .bind(entry.final_odds)
+        .bind(entry.win_payoff)
+        .bind(entry.place_payoff)
+        .bind(entry.show_payoff)
+        .bind(entry.claimed || !entry.claimed_by.is_empty())
+        .bind(&entry.claimed_by)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| format!("Failed to store result: {}", e))?;
+    }
+
+    sqlx::query("INSERT OR REPLACE INTO race_results (race_id, source, recorded_at) VALUES (?, ?, ?);")
+        .bind(race_id)
+        .bind(source)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| format!("Failed to store race result: {}", e))?;
+
+    Ok(())
+}
+
+// Every race in the file is checked before any is written, and all are written together, so a bad
+// race leaves the card's results as they were.
+pub async fn import_results_csv(
+    pool: &SqlitePool,
+    racecard_id: i64,
+    path: &str,
+) -> Result<ResultsImportSummary, String> {
+    let rows = read_csv_file(path).await?;
+
+    let mut by_race: BTreeMap<i64, Vec<RaceResultEntry>> = BTreeMap::new();
+    for row in &rows {
+        let line = row.line;
+        let race_number = parse_csv_number::<i64>(row, "race_number", line)?
+            .ok_or_else(|| format!("Line {}: race_number is required", line))?;
+        let program_number = row
+            .get("program_number")
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| format!("Line {}: program_number is required", line))?
+            .clone();
+        let claimed_by = row.get("claimed_by").cloned().unwrap_or_default();
+
+        by_race.entry(race_number).or_default().push(RaceResultEntry {
+            program_number,
+            finish_position: parse_csv_number(row, "finish_position", line)?,
+            margin: parse_csv_number(row, "margin", line)?,
+            final_odds: parse_csv_number(row, "final_odds", line)?,
+            win_payoff: parse_csv_number(row, "win_payoff", line)?,
+            place_payoff: parse_csv_number(row, "place_payoff", line)?,
+            show_payoff: parse_csv_number(row, "show_payoff", line)?,
+            claimed: !claimed_by.is_empty(),
+            claimed_by,
+        });
+    }
+
+    let source = format!(
+        "csv:{}",
+        std::path::Path::new(path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    );
+
+    let mut races = Vec::with_capacity(by_race.len());
+    for (race_number, entries) in by_race {
+        let race_id = sqlx::query_scalar::<_, i64>(
+            "SELECT id FROM races WHERE racecard_id = ? AND race_number = ?;",
+        )
+        .bind(racecard_id)
+        .bind(race_number)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Failed to look up race {}: {}", race_number, e))?
+        .ok_or_else(|| format!("Race {} does not exist on this racecard", race_number))?;
+
+        let resolved = resolve_race_result(pool, race_id, entries)
+            .await
+            .map_err(|e| format!("Race {}: {}", race_number, e))?;
+        races.push((race_number, race_id, resolved));
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let mut summary = ResultsImportSummary { races_updated: 0, entries_recorded: 0 };
+    for (race_number, race_id, resolved) in &races {
+        write_race_result(&mut tx, *race_id, resolved, &source)
+            .await
+            .map_err(|e| format!("Race {}: {}", race_number, e))?;
+
+        summary.races_updated += 1;
+        summary.entries_recorded += resolved.len();
+    }
+    tx.commit().await.map_err(|e| format!("Failed to commit results: {}", e))?;
+
+    Ok(summary)
+}
+
+pub async fn read_results_by_racecard_id(
+    pool: &SqlitePool,
+    racecard_id: i64,
+) -> Result<Vec<HorseResult>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT hr.*, r.race_number, h.program_number, h.horse_name
+         FROM horse_results hr
+         JOIN races r ON r.id = hr.race_id
+         JOIN horses h ON h.id = hr.horse_id
+         WHERE r.racecard_id = ?
+         ORDER BY r.race_number ASC, hr.finish_position IS NULL, hr.finish_position ASC;",
+    )
+    .bind(racecard_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| HorseResult {
+            race_id: row.get("race_id"),
+            race_number: row.get("race_number"),
+            horse_id: row.get("horse_id"),
+            program_number: row.get("program_number"),
+            horse_name: row.get("horse_name"),
+            finish_position: row.get("finish_position"),
+            margin: row.get("margin"),
+            final_odds: row.get("final_odds"),
+            win_payoff: row.get("win_payoff"),
+            place_payoff: row.get("place_payoff"),
+            show_payoff: row.get("show_payoff"),
+            claimed: row.get("claimed"),
+            claimed_by: row.get("claimed_by"),
+        })
+        .collect())
+}
+
+pub fn normalize_program_number(program_number: &str) -> String {
+    program_number.trim().to_uppercase()
+}
+
+fn parse_csv_number<T: std::str::FromStr>(
+    row: &CsvRow,
+    column: &str,
+    line: usize,
+) -> Result<Option<T>, String> {
+    match row.get(column).map(|v| v.trim_start_matches('$')) {
+        Some(value) if !value.is_empty() => value
+            .parse::<T>()
+            .map(Some)
+            .map_err(|_| format!("Line {}: invalid {} '{}'", line, column, value)),
+        _ => Ok(None),
+    }
+}
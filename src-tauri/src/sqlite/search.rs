@@ -0,0 +1,207 @@
+use railbreaker_lib::models::racecard::Racecard;
+use serde::{Deserialize, Serialize};
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+use tauri::State;
+use crate::analysis::non_empty;
+use crate::sqlite::racecards::read_racecard_by_id;
+use crate::sqlite::watch_list::horse_key;
+
+const DEFAULT_LIMIT: i64 = 200;
+const MAX_LIMIT: i64 = 2000;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SearchQuery {
+    pub text: Option<String>,
+    // One of horse, trainer, jockey, sire, dam or owner; every name column when omitted.
+    pub field: Option<String>,
+    pub track_code: Option<String>,
+    pub date_from: Option<String>,
+    pub date_to: Option<String>,
+    pub surface: Option<String>,
+    pub race_type: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub racecard_id: i64,
+    pub race_id: i64,
+    pub race_number: Option<i64>,
+    pub horse_id: i64,
+    pub program_number: String,
+    pub horse_name: String,
+    pub trainer: String,
+    pub jockey: String,
+    pub track_code: String,
+    pub date: String,
+    pub surface: String,
+    pub race_type: String,
+}
+
+#[tauri::command]
+pub async fn search_racecards(
+    pool: State<'_, SqlitePool>,
+    query: SearchQuery,
+) -> Result<Vec<SearchHit>, String> {
+    search(&pool, &query).await
+}
+
+pub async fn search(pool: &SqlitePool, query: &SearchQuery) -> Result<Vec<SearchHit>, String> {
+    let match_expression = match query.text.as_deref() {
+        Some(text) => build_match_expression(text, query.field.as_deref())?,
+        None => None,
+    };
+
+    let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+        "SELECT s.*, horse_search.horse_name, horse_search.trainer, horse_search.jockey
+         FROM search_entries s
+         JOIN horse_search ON horse_search.rowid = s.horse_id
+         WHERE 1 = 1",
+    );
+
+    if let Some(expression) = match_expression {
+        builder.push(" AND horse_search MATCH ").push_bind(expression);
+    }
+    if let Some(track_code) = non_empty(&query.track_code) {
+        builder.push(" AND s.track_code = ").push_bind(track_code.to_uppercase());
+    }
+    if let Some(date_from) = non_empty(&query.date_from) {
+        builder.push(" AND s.date >= ").push_bind(date_from.to_string());
+    }
+    if let Some(date_to) = non_empty(&query.date_to) {
+        builder.push(" AND s.date <= ").push_bind(date_to.to_string());
+    }
+    if let Some(surface) = non_empty(&query.surface) {
+        builder.push(" AND s.surface = ").push_bind(surface.to_string());
+    }
+    if let Some(race_type) = non_empty(&query.race_type) {
+        builder.push(" AND s.race_type = ").push_bind(race_type.to_string());
+    }
+
+    builder
+        .push(" ORDER BY s.date DESC, s.track_code ASC, s.race_number ASC, s.program_number ASC LIMIT ")
+        .push_bind(query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT));
+
+    let rows = builder
+        .build()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Failed to search racecards: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| SearchHit {
+            racecard_id: row.get("racecard_id"),
+            race_id: row.get("race_id"),
+            race_number: row.get("race_number"),
+            horse_id: row.get("horse_id"),
+            program_number: row.get("program_number"),
+            horse_name: row.get("horse_name"),
+            trainer: row.get("trainer"),
+            jockey: row.get("jockey"),
+            track_code: row.get("track_code"),
+            date: row.get("date"),
+            surface: row.get("surface"),
+            race_type: row.get("race_type"),
+        })
+        .collect())
+}
+
+// User text is never passed to FTS5 as syntax. Each word becomes a quoted prefix term, so
+// "bob baff" finds "Bob Baffert" and stray quotes or operators cannot break the query.
+fn build_match_expression(text: &str, field: Option<&str>) -> Result<Option<String>, String> {
+    let terms: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect();
+
+    if terms.is_empty() {
+        return Ok(None);
+    }
+
+    let terms = terms.join(" AND ");
+    let column = match field.map(|f| f.to_lowercase()) {
+        None => return Ok(Some(terms)),
+        Some(f) if f.is_empty() || f == "all" => return Ok(Some(terms)),
+        Some(f) if f == "horse" || f == "horse_name" => "horse_name",
+        Some(f) if f == "trainer" => "trainer",
+        Some(f) if f == "jockey" => "jockey",
+        Some(f) if f == "sire" => "sire",
+        Some(f) if f == "dam" => "dam",
+        Some(f) if f == "owner" => "owner",
+        Some(f) => return Err(format!("Unknown search field: {}", f)),
+    };
+
+    Ok(Some(format!("{} : ({})", column, terms)))
+}
+
+pub async fn index_racecard(pool: &SqlitePool, racecard: &Racecard) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    for race in &racecard.races {
+        for horse in &race.horses {
+            sqlx::query(
+                "INSERT OR REPLACE INTO search_entries
+                    (horse_id, race_id, racecard_id, race_number, program_number, horse_key, track_code, date, surface, race_type)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?);",
+            )
+            .bind(horse.id)
+            .bind(race.id)
+            .bind(racecard.id)
+            .bind(race.race_number.map(|n| n as i64))
+            .bind(&horse.program_number)
+            .bind(horse_key(horse))
+            .bind(&racecard.track_code)
+            .bind(&racecard.date)
+            .bind(&race.surface)
+            .bind(&race.race_type)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query("DELETE FROM horse_search WHERE rowid = ?;")
+                .bind(horse.id)
+                .execute(&mut *tx)
+                .await?;
+
+            sqlx::query(
+                "INSERT INTO horse_search (rowid, horse_name, trainer, jockey, sire, dam, owner)
+                 VALUES (?, ?, ?, ?, ?, ?, ?);",
+            )
+            .bind(horse.id)
+            .bind(&horse.horse_name)
+            .bind(&horse.todays_trainer)
+            .bind(&horse.todays_jockey)
+            .bind(&horse.sire)
+            .bind(&horse.dam)
+            .bind(&horse.todays_owner)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+
+    tx.commit().await
+}
+
+// Cards stored before the index existed (or whose indexing failed) are picked up at startup.
+pub async fn index_missing_racecards(pool: &SqlitePool) -> Result<usize, String> {
+    let racecard_ids = sqlx::query_scalar::<_, i64>(
+        "SELECT id FROM racecards
+         WHERE id NOT IN (SELECT DISTINCT racecard_id FROM search_entries);",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Failed to find unindexed racecards: {}", e))?;
+
+    for racecard_id in &racecard_ids {
+        let racecard = read_racecard_by_id(pool, *racecard_id)
+            .await
+            .map_err(|e| format!("Failed to load racecard {}: {}", racecard_id, e))?;
+        index_racecard(pool, &racecard)
+            .await
+            .map_err(|e| format!("Failed to index racecard {}: {}", racecard_id, e))?;
+    }
+
+    Ok(racecard_ids.len())
+}
@@ -0,0 +1,291 @@
+use railbreaker_lib::models::racecard::{Horse, Racecard};
+use serde::Serialize;
+use sqlx::{Row, SqlitePool};
+use tauri::{AppHandle, Emitter, State};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchListEntry {
+    pub horse_key: String,
+    pub horse_name: String,
+    pub year_of_birth: Option<i64>,
+    pub dam: String,
+    pub note: String,
+    pub added_at: String,
+    pub updated_at: String,
+    pub appearances: i64,
+    pub last_seen_date: Option<String>,
+    pub last_seen_track_code: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchAlert {
+    pub horse_key: String,
+    pub horse_id: i64,
+    pub horse_name: String,
+    pub race_number: Option<i64>,
+    pub program_number: String,
+    pub note: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchedHorsesEvent {
+    pub racecard_id: i64,
+    pub track: String,
+    pub date: String,
+    pub horses: Vec<WatchAlert>,
+}
+
+#[tauri::command]
+pub async fn add_to_watch_list(
+    pool: State<'_, SqlitePool>,
+    horse_id: i64,
+) -> Result<WatchListEntry, String> {
+    watch_horse(&pool, horse_id).await
+}
+
+#[tauri::command]
+pub async fn remove_from_watch_list(
+    pool: State<'_, SqlitePool>,
+    horse_key: String,
+) -> Result<(), String> {
+    sqlx::query("DELETE FROM watch_list WHERE horse_key = ?;")
+        .bind(horse_key)
+        .execute(&*pool)
+        .await
+        .map_err(|e| format!("Failed to remove horse from watch list: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_watch_list(pool: State<'_, SqlitePool>) -> Result<Vec<WatchListEntry>, String> {
+    read_watch_list(&pool, None)
+        .await
+        .map_err(|e| format!("Failed to load watch list: {}", e))
+}
+
+// Returns the watch list key when the horse is watched, so the UI can remove it again.
+#[tauri::command]
+pub async fn get_watch_status(
+    pool: State<'_, SqlitePool>,
+    horse_id: i64,
+) -> Result<Option<String>, String> {
+    sqlx::query_scalar::<_, String>(
+        "SELECT w.horse_key FROM watch_list w
+         JOIN search_entries s ON s.horse_key = w.horse_key
+         WHERE s.horse_id = ?;",
+    )
+    .bind(horse_id)
+    .fetch_optional(&*pool)
+    .await
+    .map_err(|e| format!("Failed to read watch status: {}", e))
+}
+
+// Name + year of birth + dam is unique in practice; names alone are reused across generations.
+pub fn horse_key(horse: &Horse) -> String {
+    format!(
+        "{}|{}|{}",
+        normalize_name(&horse.horse_name),
+        horse.year_of_birth.map(|y| (y as i64).to_string()).unwrap_or_default(),
+        normalize_name(&horse.dam)
+    )
+}
+
+fn normalize_name(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_uppercase()
+}
+
+pub async fn watch_horse(pool: &SqlitePool, horse_id: i64) -> Result<WatchListEntry, String> {
+    let row = sqlx::query(
+        "SELECT s.horse_key, s.date, h.horse_name, h.year_of_birth, h.dam, h.note
+         FROM horses h
+         JOIN search_entries s ON s.horse_id = h.id
+         WHERE h.id = ?;",
+    )
+    .bind(horse_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to load horse: {}", e))?
+    .ok_or_else(|| format!("Horse {} is not in the database", horse_id))?;
+
+    let horse_key: String = row.get("horse_key");
+    let note: String = row.get("note");
+    let now = chrono::Utc::now().to_rfc3339();
+
+    // Re-watching keeps the existing note unless this card has one of its own.
+    sqlx::query(
+        "INSERT INTO watch_list (horse_key, horse_name, year_of_birth, dam, note, added_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?)
+         ON CONFLICT(horse_key) DO UPDATE SET
+            note = CASE WHEN excluded.note <> '' THEN excluded.note ELSE watch_list.note END,
+            updated_at = excluded.updated_at;",
+    )
+    .bind(&horse_key)
+    .bind(row.get::<String, _>("horse_name"))
+    .bind(row.get::<Option<i64>, _>("year_of_birth"))
+    .bind(row.get::<String, _>("dam"))
+    .bind(&note)
+    .bind(&now)
+    .bind(&now)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to add horse to watch list: {}", e))?;
+
+    carry_note_forward(pool, &horse_key, &row.get::<String, _>("date"))
+        .await
+        .map_err(|e| format!("Failed to copy note to later cards: {}", e))?;
+
+    read_watch_list(pool, Some(&horse_key))
+        .await
+        .map_err(|e| format!("Failed to load watch list: {}", e))?
+        .pop()
+        .ok_or_else(|| "Failed to load watch list entry".to_string())
+}
+
+// Cards already stored for later dates pick up the note too, e.g. tomorrow's card imported early.
+async fn carry_note_forward(pool: &SqlitePool, horse_key: &str, from_date: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE horses SET note = (SELECT note FROM watch_list WHERE horse_key = ?)
+         WHERE note = ''
+           AND id IN (SELECT horse_id FROM search_entries WHERE horse_key = ? AND date > ?);",
+    )
+    .bind(horse_key)
+    .bind(horse_key)
+    .bind(from_date)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Called from update_note so the latest note on any card becomes the one carried forward.
+pub async fn sync_watch_note(pool: &SqlitePool, horse_id: i64, note: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE watch_list SET note = ?, updated_at = ?
+         WHERE horse_key = (SELECT horse_key FROM search_entries WHERE horse_id = ?);",
+    )
+    .bind(note)
+    .bind(chrono::Utc::now().to_rfc3339())
+    .bind(horse_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Copies watch list notes onto a newly stored card. The card must already be indexed.
+pub async fn apply_watch_notes(pool: &SqlitePool, racecard: &mut Racecard) -> Result<(), sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT s.horse_id, w.note
+         FROM search_entries s
+         JOIN watch_list w ON w.horse_key = s.horse_key
+         WHERE s.racecard_id = ? AND w.note <> '';",
+    )
+    .bind(racecard.id)
+    .fetch_all(pool)
+    .await?;
+
+    for row in rows {
+        let horse_id: i64 = row.get("horse_id");
+        let note: String = row.get("note");
+
+        let horse = racecard
+            .races
+            .iter_mut()
+            .flat_map(|race| race.horses.iter_mut())
+            .find(|horse| horse.id == horse_id);
+
+        if let Some(horse) = horse {
+            if horse.note.is_empty() {
+                sqlx::query("UPDATE horses SET note = ? WHERE id = ?;")
+                    .bind(&note)
+                    .bind(horse_id)
+                    .execute(pool)
+                    .await?;
+                horse.note = note;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn watched_horses_on_racecard(pool: &SqlitePool, racecard_id: i64) -> Result<Vec<WatchAlert>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT s.horse_key, s.horse_id, s.race_number, s.program_number, w.horse_name, w.note
+         FROM search_entries s
+         JOIN watch_list w ON w.horse_key = s.horse_key
+         WHERE s.racecard_id = ?
+         ORDER BY s.race_number ASC, s.program_number ASC;",
+    )
+    .bind(racecard_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| WatchAlert {
+            horse_key: row.get("horse_key"),
+            horse_id: row.get("horse_id"),
+            horse_name: row.get("horse_name"),
+            race_number: row.get("race_number"),
+            program_number: row.get("program_number"),
+            note: row.get("note"),
+        })
+        .collect())
+}
+
+// `horses` comes from after_racecard_added, which checks the watch list for every stored card.
+pub fn notify_watched_horses(app: &AppHandle, racecard: &Racecard, horses: Vec<WatchAlert>) {
+    if horses.is_empty() {
+        return;
+    }
+
+    let _ = app.emit(
+        "watched-horses-found",
+        WatchedHorsesEvent {
+            racecard_id: racecard.id,
+            track: racecard.track.clone(),
+            date: racecard.date.clone(),
+            horses,
+        },
+    );
+}
+
+async fn read_watch_list(pool: &SqlitePool, horse_key: Option<&str>) -> Result<Vec<WatchListEntry>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT w.*,
+            (SELECT COUNT(*) FROM search_entries s WHERE s.horse_key = w.horse_key) AS appearances,
+            (SELECT s.date FROM search_entries s WHERE s.horse_key = w.horse_key
+                ORDER BY s.date DESC LIMIT 1) AS last_seen_date,
+            (SELECT s.track_code FROM search_entries s WHERE s.horse_key = w.horse_key
+                ORDER BY s.date DESC LIMIT 1) AS last_seen_track_code
+         FROM watch_list w
+         WHERE ? IS NULL OR w.horse_key = ?
+         ORDER BY w.horse_name ASC;",
+    )
+    .bind(horse_key)
+    .bind(horse_key)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| WatchListEntry {
+            horse_key: row.get("horse_key"),
+            horse_name: row.get("horse_name"),
+            year_of_birth: row.get("year_of_birth"),
+            dam: row.get("dam"),
+            note: row.get("note"),
+            added_at: row.get("added_at"),
+            updated_at: row.get("updated_at"),
+            appearances: row.get("appearances"),
+            last_seen_date: row.get("last_seen_date"),
+            last_seen_track_code: row.get("last_seen_track_code"),
+        })
+        .collect())
+}
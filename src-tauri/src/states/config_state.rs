@@ -1,4 +1,8 @@
 use serde::{Deserialize, Serialize};
+use crate::analysis::fair_odds::FairOddsSettings;
+use crate::analysis::figures::FigureSettings;
+use crate::analysis::model_parameters::ModelParameters;
+use crate::analysis::trip_model::TripWeights;
 use crate::constants::HORSE_SORTING_METHOD_DEFAULT;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -9,6 +13,18 @@ pub struct ConfigState {
     pub window_width: Option<f64>,
     pub window_height: Option<f64>,
     pub horse_sorting_method: String,
+    #[serde(default)]
+    pub auto_import_enabled: bool,
+    #[serde(default)]
+    pub auto_import_directory: Option<String>,
+    #[serde(default)]
+    pub trip_weights: TripWeights,
+    #[serde(default)]
+    pub model_parameters: ModelParameters,
+    #[serde(default)]
+    pub fair_odds: FairOddsSettings,
+    #[serde(default)]
+    pub figures: FigureSettings,
 }
 
 impl Default for ConfigState {
@@ -20,6 +36,12 @@ impl Default for ConfigState {
             window_width: None,
             window_height: None,
             horse_sorting_method: HORSE_SORTING_METHOD_DEFAULT.to_string(),
+            auto_import_enabled: false,
+            auto_import_directory: None,
+            trip_weights: TripWeights::default(),
+            model_parameters: ModelParameters::default(),
+            fair_odds: FairOddsSettings::default(),
+            figures: FigureSettings::default(),
         }
     }
 }
\ No newline at end of file
@@ -1,13 +1,18 @@
 use std::{sync::{Mutex, OnceLock}};
 use std::collections::HashMap;
 use serde::Serialize;
+use crate::tracks::Track;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct GlobalState {
-    pub tracks: HashMap<String, String>,
+    // Served by get_tracks/get_track rather than with the rest of the global state.
+    #[serde(skip)]
+    pub tracks: HashMap<String, Track>,
     pub current_directory: String,
     pub downloads_directory: String,
     pub racecards_directory: String,
+    pub schema_version: i64,
+    pub database_error: Option<String>,
 }
 
 impl Default for GlobalState {
@@ -17,6 +22,8 @@ impl Default for GlobalState {
             current_directory: String::new(),
             downloads_directory: String::new(),
             racecards_directory: String::new(),
+            schema_version: 0,
+            database_error: None,
         }
     }
 }
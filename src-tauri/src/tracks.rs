@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::states::global_state::global_state;
+
+const BUNDLED_TRACKS: &str = include_str!("../resources/tracks.csv");
+pub const TRACKS_FILE_NAME: &str = "tracks.csv";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Track {
+    pub code: String,
+    pub name: String,
+    pub country: String,
+    pub timezone: String,
+    pub surfaces: Vec<String>,
+    // Furlongs; None when the file leaves the column blank.
+    pub dirt_circumference: Option<f64>,
+    pub turf_circumference: Option<f64>,
+    pub chutes: Vec<String>,
+    // Other codes (e.g. Brisnet's) that refer to this track.
+    pub aliases: Vec<String>,
+}
+
+// Bundled tracks first, then rows from <working directory>/tracks.csv replace or add by code.
+// Blank columns in a user row keep the bundled value, so a row can correct just one field.
+// A broken user file is reported and skipped rather than leaving the app without tracks.
+pub fn load_tracks(current_directory: &Path) -> Result<HashMap<String, Track>, String> {
+    let mut tracks = parse_tracks(BUNDLED_TRACKS)
+        .map_err(|e| format!("Failed to parse bundled tracks: {}", e))?;
+
+    let user_path = current_directory.join(TRACKS_FILE_NAME);
+    if user_path.exists() {
+        let user_tracks = std::fs::read_to_string(&user_path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| parse_tracks(&content));
+
+        match user_tracks {
+            Ok(user_tracks) => {
+                for (code, track) in user_tracks {
+                    let track = match tracks.remove(&code) {
+                        Some(bundled) => merge_track(track, bundled),
+                        None => track,
+                    };
+                    tracks.insert(code, track);
+                }
+            }
+            Err(e) => eprintln!("Failed to load track file '{}': {}", user_path.display(), e),
+        }
+    }
+
+    Ok(tracks)
+}
+
+pub fn init_tracks(current_directory: &Path) -> Result<usize, String> {
+    let tracks = load_tracks(current_directory)?;
+    let count = tracks.len();
+    global_state()
+        .lock()
+        .map_err(|e| format!("Failed to lock global state: {}", e))?
+        .tracks = tracks;
+    Ok(count)
+}
+
+pub fn parse_tracks(content: &str) -> Result<HashMap<String, Track>, String> {
+    let mut tracks = HashMap::new();
+
+    for row in crate::files::parse_csv(content)? {
+        let field = |name: &str| row.get(name).map(|v| v.trim().to_string()).unwrap_or_default();
+        let code = field("code").to_uppercase();
+        if code.is_empty() {
+            return Err(format!("Line {} has no track code", row.line));
+        }
+
+        let track = Track {
+            name: field("name"),
+            country: field("country"),
+            timezone: field("timezone"),
+            surfaces: split_list(&field("surfaces")),
+            dirt_circumference: parse_furlongs(&field("dirt_circumference"), &code)?,
+            turf_circumference: parse_furlongs(&field("turf_circumference"), &code)?,
+            chutes: split_list(&field("chutes")),
+            aliases: split_list(&field("aliases")).into_iter().map(|a| a.to_uppercase()).collect(),
+            code: code.clone(),
+        };
+        tracks.insert(code, track);
+    }
+
+    Ok(tracks)
+}
+
+fn merge_track(user: Track, bundled: Track) -> Track {
+    let or = |value: String, fallback: String| if value.is_empty() { fallback } else { value };
+    let or_list = |value: Vec<String>, fallback: Vec<String>| if value.is_empty() { fallback } else { value };
+
+    Track {
+        code: user.code,
+        name: or(user.name, bundled.name),
+        country: or(user.country, bundled.country),
+        timezone: or(user.timezone, bundled.timezone),
+        surfaces: or_list(user.surfaces, bundled.surfaces),
+        dirt_circumference: user.dirt_circumference.or(bundled.dirt_circumference),
+        turf_circumference: user.turf_circumference.or(bundled.turf_circumference),
+        chutes: or_list(user.chutes, bundled.chutes),
+        aliases: or_list(user.aliases, bundled.aliases),
+    }
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split('|')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn parse_furlongs(value: &str, code: &str) -> Result<Option<f64>, String> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    value
+        .parse::<f64>()
+        .map(Some)
+        .map_err(|_| format!("Invalid circumference '{}' for track {}", value, code))
+}
+
+pub fn find_track<'a>(tracks: &'a HashMap<String, Track>, code: &str) -> Option<&'a Track> {
+    let code = code.trim().to_uppercase();
+    if code.is_empty() {
+        return None;
+    }
+    tracks
+        .get(&code)
+        .or_else(|| tracks.values().find(|track| track.aliases.contains(&code)))
+}
+
+// Adds a `track_name` next to each past performance's track code in a serialized racecard.
+// The racecard model belongs to railbreaker-lib, so the name travels in the JSON only.
+pub fn resolve_track_names(racecard: &mut Value) {
+    let gs = match global_state().lock() {
+        Ok(gs) => gs,
+        Err(_) => return,
+    };
+    let tracks = &gs.tracks;
+
+    if let Some(Value::String(track)) = racecard.get("track") {
+        if track.is_empty() {
+            let code = racecard.get("track_code").and_then(Value::as_str).unwrap_or_default();
+            if let Some(found) = find_track(tracks, code) {
+                racecard["track"] = Value::String(found.name.clone());
+            }
+        }
+    }
+
+    let races = match racecard.get_mut("races").and_then(Value::as_array_mut) {
+        Some(races) => races,
+        None => return,
+    };
+
+    let past_performances = races
+        .iter_mut()
+        .filter_map(|race| race.get_mut("horses").and_then(Value::as_array_mut))
+        .flatten()
+        .filter_map(|horse| horse.get_mut("past_performances").and_then(Value::as_array_mut))
+        .flatten();
+
+    for pp in past_performances {
+        let name = ["track_code", "bris_track_code"]
+            .iter()
+            .filter_map(|key| pp.get(*key).and_then(Value::as_str))
+            .find_map(|code| find_track(tracks, code))
+            .map(|track| track.name.clone())
+            .unwrap_or_default();
+
+        if let Some(pp) = pp.as_object_mut() {
+            pp.insert("track_name".to_string(), Value::String(name));
+        }
+    }
+}
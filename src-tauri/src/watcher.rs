@@ -0,0 +1,231 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tauri::{async_runtime::JoinHandle, AppHandle, Emitter, Manager};
+use crate::commands::process_zip_file_commands::ExtractStatus;
+use crate::import::import_racecard_files;
+use crate::sqlite::auto_import::{mark_zip_handled, read_handled_zips};
+use crate::sqlite::watch_list::notify_watched_horses;
+use crate::states::config_state::ConfigState;
+use crate::states::global_state::global_state;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+// A zip whose import failed is tried again after this long, unless it changes first.
+const RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+// Path, size and modification time in milliseconds; a zip that changes is a new zip.
+type ZipKey = (PathBuf, i64, i64);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RacecardImportedEvent {
+    pub racecard_id: i64,
+    pub zip_file_name: String,
+    pub track: String,
+    pub date: String,
+}
+
+struct Watcher {
+    directory: String,
+    handle: JoinHandle<()>,
+}
+
+static WATCHER: OnceLock<Mutex<Option<Watcher>>> = OnceLock::new();
+
+fn watcher() -> &'static Mutex<Option<Watcher>> {
+    WATCHER.get_or_init(|| Mutex::new(None))
+}
+
+// Falls back to the downloads folder when no folder has been chosen.
+pub fn auto_import_directory(cfg: &ConfigState) -> Result<String, String> {
+    match &cfg.auto_import_directory {
+        Some(directory) => Ok(directory.clone()),
+        None => {
+            let global_state = global_state()
+                .lock()
+                .map_err(|e| format!("Failed to lock global state: {}", e))?;
+            Ok(global_state.downloads_directory.clone())
+        }
+    }
+}
+
+pub fn start_watcher(app: AppHandle, directory: String) -> Result<(), String> {
+    if !Path::new(&directory).is_dir() {
+        return Err(format!("{} is not a directory", directory));
+    }
+
+    let mut current = watcher()
+        .lock()
+        .map_err(|e| format!("Failed to lock watcher: {}", e))?;
+
+    if let Some(existing) = current.take() {
+        existing.handle.abort();
+    }
+
+    let handle = tauri::async_runtime::spawn(watch_directory(app, PathBuf::from(&directory)));
+    *current = Some(Watcher { directory, handle });
+
+    Ok(())
+}
+
+pub fn stop_watcher() -> Result<(), String> {
+    let mut current = watcher()
+        .lock()
+        .map_err(|e| format!("Failed to lock watcher: {}", e))?;
+
+    if let Some(existing) = current.take() {
+        existing.handle.abort();
+    }
+
+    Ok(())
+}
+
+pub fn watched_directory() -> Option<String> {
+    watcher()
+        .lock()
+        .ok()
+        .and_then(|current| current.as_ref().map(|w| w.directory.clone()))
+}
+
+async fn watch_directory(app: AppHandle, directory: PathBuf) {
+    // Zips already in the folder are imported too, so downloads that arrived while the app was
+    // closed are not missed. Zips handled on an earlier run are remembered in the database, so a
+    // card the user has deleted is not imported again from its download.
+    let mut handled: HashSet<ZipKey> = HashSet::new();
+    if let Some(pool) = app.try_state::<SqlitePool>() {
+        match read_handled_zips(&pool).await {
+            Ok(rows) => handled.extend(
+                rows.into_iter()
+                    .map(|(path, size, modified)| (PathBuf::from(path), size, modified)),
+            ),
+            Err(e) => eprintln!("Failed to read auto-imported zips: {}", e),
+        }
+    }
+    let mut retry_at: HashMap<ZipKey, Instant> = HashMap::new();
+    let mut pending: HashMap<PathBuf, (i64, i64)> = HashMap::new();
+
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+
+        let mut seen = HashMap::new();
+        for key in scan_zip_files(&directory) {
+            if handled.contains(&key) || retry_at.get(&key).is_some_and(|at| Instant::now() < *at) {
+                continue;
+            }
+            let (path, size, modified) = key.clone();
+
+            // A download is only picked up once its size and timestamp hold still for a full poll
+            // and the archive can actually be opened; browsers write large files in chunks.
+            if pending.get(&path) != Some(&(size, modified)) {
+                seen.insert(path, (size, modified));
+                continue;
+            }
+            match zip_has_racecards(&path) {
+                None => {
+                    seen.insert(path, (size, modified));
+                }
+                // Downloads folders hold plenty of zips that aren't racecards.
+                Some(false) => {
+                    mark_handled(&app, &mut handled, key).await;
+                }
+                Some(true) => {
+                    if import_new_zip(&app, &path).await {
+                        retry_at.remove(&key);
+                        mark_handled(&app, &mut handled, key).await;
+                    } else {
+                        retry_at.insert(key, Instant::now() + RETRY_INTERVAL);
+                    }
+                }
+            }
+        }
+
+        pending = seen;
+    }
+}
+
+async fn mark_handled(app: &AppHandle, handled: &mut HashSet<ZipKey>, key: ZipKey) {
+    if let Some(pool) = app.try_state::<SqlitePool>() {
+        let (path, size, modified) = &key;
+        if let Err(e) = mark_zip_handled(&pool, &path.to_string_lossy(), *size, *modified).await {
+            eprintln!("Failed to record auto-imported zip {}: {}", path.display(), e);
+        }
+    }
+    handled.insert(key);
+}
+
+// True when every card in the zip is now stored, whether by this import or an earlier one.
+async fn import_new_zip(app: &AppHandle, path: &Path) -> bool {
+    let pool = match app.try_state::<SqlitePool>() {
+        Some(pool) => pool,
+        None => return false,
+    };
+
+    match import_racecard_files(&pool, &path.to_string_lossy()).await {
+        Ok(files) => {
+            let mut complete = true;
+            for imported in files {
+                match imported.racecard {
+                    Some(racecard) => {
+                        let _ = app.emit(
+                            "racecard-imported",
+                            RacecardImportedEvent {
+                                racecard_id: racecard.id,
+                                zip_file_name: imported.file.zip_file_name,
+                                track: racecard.track.clone(),
+                                date: racecard.date.clone(),
+                            },
+                        );
+                        notify_watched_horses(app, &racecard, imported.watched);
+                    }
+                    None if imported.file.status == ExtractStatus::Failed => {
+                        complete = false;
+                        eprintln!("Auto-import of {} failed: {}", imported.file.zip_file_name, imported.file.message);
+                    }
+                    None => {}
+                }
+            }
+            complete
+        }
+        Err(e) => {
+            eprintln!("Auto-import of {} failed: {}", path.display(), e);
+            false
+        }
+    }
+}
+
+fn scan_zip_files(directory: &Path) -> Vec<ZipKey> {
+    let entries = match std::fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    entries
+        .flatten()
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .map(|ext| ext.eq_ignore_ascii_case("zip"))
+                .unwrap_or(false)
+        })
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            if !metadata.is_file() {
+                return None;
+            }
+            let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+            Some((entry.path(), metadata.len() as i64, modified.as_millis() as i64))
+        })
+        .collect()
+}
+
+// None while the archive can't be opened yet, otherwise whether it holds any .DRF files.
+fn zip_has_racecards(path: &Path) -> Option<bool> {
+    let archive = std::fs::File::open(path)
+        .ok()
+        .and_then(|file| zip::ZipArchive::new(file).ok())?;
+    let has_racecards = archive.file_names().any(|name| name.to_lowercase().ends_with(".drf"));
+    Some(has_racecards)
+}
@@ -259,3 +259,11 @@ export class RaceMeta {
     };
   }
 }
+
+// Sent by the backend after it reranks a race whose scratches changed.
+export interface RaceMetaUpdate {
+  racecard_id: number;
+  race_id: number;
+  race_number: number | null;
+  meta: any;
+}
@@ -0,0 +1,36 @@
+export type BetType =
+  | "Exacta"
+  | "Trifecta"
+  | "Superfecta"
+  | "DailyDouble"
+  | "Pick3"
+  | "Pick4"
+  | "Pick5"
+  | "Pick6";
+
+export type TicketStructure = "PartWheel" | "Box" | "KeyWith";
+
+// Selections are program numbers, "ALL" or "TOPn" (the model's top n in that race).
+export interface TicketRequest {
+  bet_type: BetType;
+  race_number: number;
+  base_amount: number;
+  structure: TicketStructure;
+  selections: string[][];
+  note?: string;
+}
+
+export interface BetTicket {
+  id: number | null;
+  racecard_id: number;
+  bet_type: BetType;
+  race_number: number;
+  base_amount: number;
+  structure: TicketStructure;
+  selections: string[][];
+  legs: string[][];
+  combinations: number;
+  total_cost: number;
+  note: string;
+  created_at: string | null;
+}
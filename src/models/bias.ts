@@ -0,0 +1,26 @@
+export interface BiasBucket {
+  label: string;
+  starts: number;
+  wins: number;
+  win_pct: number;
+  impact: number;
+}
+
+export interface BiasReport {
+  track_code: string;
+  surface: string | null;
+  distance_band: string | null;
+  condition: string | null;
+  races: number;
+  starts: number;
+  by_post: BiasBucket[];
+  by_post_group: BiasBucket[];
+  by_first_call: BiasBucket[];
+  flags: string[];
+}
+
+export interface RaceBias {
+  race_id: number;
+  race_number: number | null;
+  bias: BiasReport;
+}
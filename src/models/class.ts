@@ -0,0 +1,46 @@
+export type ClassMove = "drop" | "same" | "rise";
+
+export type ClaimMove =
+  | "first_time_claiming"
+  | "maiden_drop"
+  | "tag_drop"
+  | "tag_rise"
+  | "out_of_claiming";
+
+export interface LineClass {
+  pp_id: number;
+  race_date: string;
+  track_code: string;
+  race_type: string;
+  purse: number | null;
+  claiming_price: number | null;
+  level: number | null;
+  finish_position: string;
+}
+
+export interface HorseClass {
+  horse_id: number;
+  program_number: string;
+  horse_name: string;
+  last_level: number | null;
+  recent_level: number | null;
+  best_level: number | null;
+  class_change: number | null;
+  class_move: ClassMove | null;
+  claiming_price: number | null;
+  last_claiming_price: number | null;
+  claim_move: ClaimMove | null;
+  claimed_last_out: boolean;
+  lines: LineClass[];
+}
+
+// Levels are 10 points per doubling of the purse ($10,000 = 0), moved up or down by race type.
+export interface RaceClass {
+  race_number: number | null;
+  race_type: string;
+  purse: number | null;
+  claiming_price: number | null;
+  level: number | null;
+  bris_speed_for_class: number | null;
+  horses: HorseClass[];
+}
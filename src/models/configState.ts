@@ -1,3 +1,8 @@
+import type { FairOddsSettings } from './fairOdds';
+import type { FigureSettings } from './figures';
+import type { ModelParameters } from './modelParameters';
+import type { TripWeights } from './trip';
+
 export class ConfigState {
   public lastDirectory: string;
   public windowX: number | null;
@@ -5,6 +10,12 @@ export class ConfigState {
   public windowWidth: number | null;
   public windowHeight: number | null;
   public horseSortingMethod: string;
+  public autoImportEnabled: boolean;
+  public autoImportDirectory: string | null;
+  public tripWeights: TripWeights | null;
+  public modelParameters: ModelParameters | null;
+  public fairOdds: FairOddsSettings | null;
+  public figures: FigureSettings | null;
 
   constructor(lastDirectory: string = '') {
     this.lastDirectory = lastDirectory;
@@ -13,6 +24,12 @@ export class ConfigState {
     this.windowWidth = null;
     this.windowHeight = null;
     this.horseSortingMethod = 'program-number';
+    this.autoImportEnabled = false;
+    this.autoImportDirectory = null;
+    this.tripWeights = null;
+    this.modelParameters = null;
+    this.fairOdds = null;
+    this.figures = null;
   }
 
   static fromObject(obj: any): ConfigState {
@@ -23,6 +40,12 @@ export class ConfigState {
     cs.windowHeight = obj.window_height ?? obj.windowHeight ?? null;
     cs.horseSortingMethod =
       obj.horse_sorting_method ?? obj.horseSortingMethod ?? 'program-number';
+    cs.autoImportEnabled = obj.auto_import_enabled ?? obj.autoImportEnabled ?? false;
+    cs.autoImportDirectory = obj.auto_import_directory ?? obj.autoImportDirectory ?? null;
+    cs.tripWeights = obj.trip_weights ?? obj.tripWeights ?? null;
+    cs.modelParameters = obj.model_parameters ?? obj.modelParameters ?? null;
+    cs.fairOdds = obj.fair_odds ?? obj.fairOdds ?? null;
+    cs.figures = obj.figures ?? null;
     return cs;
   }
 
@@ -34,6 +57,12 @@ export class ConfigState {
       window_width: this.windowWidth,
       window_height: this.windowHeight,
       horse_sorting_method: this.horseSortingMethod,
+      auto_import_enabled: this.autoImportEnabled,
+      auto_import_directory: this.autoImportDirectory,
+      trip_weights: this.tripWeights ?? undefined,
+      model_parameters: this.modelParameters ?? undefined,
+      fair_odds: this.fairOdds ?? undefined,
+      figures: this.figures ?? undefined,
     };
   }
 }
@@ -0,0 +1,31 @@
+export interface ConnectionQuery {
+  trainer?: string | null;
+  jockey?: string | null;
+  track_code?: string | null;
+  date_from?: string | null;
+  date_to?: string | null;
+}
+
+export interface ConnectionBucket {
+  label: string;
+  starts: number;
+  wins: number;
+  in_the_money: number;
+  win_pct: number;
+  bets: number;
+  wagered: number;
+  returned: number;
+  roi: number;
+}
+
+export interface ConnectionReport {
+  trainer: string | null;
+  jockey: string | null;
+  overall: ConnectionBucket;
+  by_surface: ConnectionBucket[];
+  by_distance: ConnectionBucket[];
+  by_class: ConnectionBucket[];
+  by_layoff: ConnectionBucket[];
+  by_first_lasix: ConnectionBucket[];
+  by_equipment_change: ConnectionBucket[];
+}
@@ -0,0 +1,14 @@
+export type ExportFormat = 'csv' | 'json';
+export type ExportFieldSet = 'summary' | 'handicapping' | 'full';
+
+export interface ExportSelection {
+  raceNumbers: number[];
+  format: ExportFormat;
+  fieldSet: ExportFieldSet;
+}
+
+export interface ExportReport {
+  path: string;
+  races: number;
+  horses: number;
+}
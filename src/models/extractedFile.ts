@@ -0,0 +1,10 @@
+export type ExtractStatus = 'extracted' | 'skipped' | 'failed';
+
+export interface ExtractedFile {
+  source: string;
+  file_name: string;
+  zip_file_name: string;
+  path: string | null;
+  status: ExtractStatus;
+  message: string;
+}
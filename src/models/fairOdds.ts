@@ -0,0 +1,29 @@
+export type OddsValue = "overlay" | "fair" | "underlay";
+
+export interface FairOddsSettings {
+  takeout: number;
+  score_scale: number;
+  overlay_margin: number;
+}
+
+export interface HorseOdds {
+  horse_id: number;
+  program_number: string;
+  horse_name: string;
+  score: number | null;
+  probability: number | null;
+  fair_odds: number | null;
+  line_odds: number | null;
+  morning_line_odds: number | null;
+  morning_line_value: OddsValue | null;
+  live_odds: number | null;
+  live_value: OddsValue | null;
+  public_probability: number | null;
+  edge: number | null;
+}
+
+export interface FairOddsLine {
+  race_number: number | null;
+  settings: FairOddsSettings;
+  horses: HorseOdds[];
+}
@@ -0,0 +1,54 @@
+export type VariantMode = "ignore" | "brisnet";
+
+export interface DistancePar {
+  furlongs: number;
+  seconds: number;
+}
+
+export interface FigureSettings {
+  base_figure: number;
+  lengths_per_second: number;
+  points_per_length: number;
+  reference_furlongs: number;
+  pars: DistancePar[];
+  turf_pars: DistancePar[];
+  variant_mode: VariantMode;
+  variant_baseline: number;
+  variant_weight: number;
+}
+
+// Our figures for one PP line, next to the Brisnet numbers for the same start.
+export interface PpFigures {
+  pp_id: number;
+  race_date: string;
+  track_code: string;
+  race_number: number | null;
+  surface: string;
+  distance_furlongs: number | null;
+  final_time: number | null;
+  horse_time: number | null;
+  variant_adjustment: number;
+  speed_figure: number | null;
+  early_pace_figure: number | null;
+  middle_pace_figure: number | null;
+  late_pace_figure: number | null;
+  bris_speed_rating: number | null;
+  speed_rating: number | null;
+  bris_early_pace: number | null;
+  bris_middle_pace: number | null;
+  bris_late_pace: number | null;
+  track_variant: number | null;
+}
+
+export interface HorseFigures {
+  horse_id: number;
+  program_number: string;
+  horse_name: string;
+  lines: PpFigures[];
+}
+
+export interface RaceFigures {
+  race_number: number | null;
+  settings: FigureSettings;
+  horses: HorseFigures[];
+}
@@ -0,0 +1,42 @@
+export type FormPattern =
+  | "Improving"
+  | "Peaked"
+  | "SecondOffLayoff"
+  | "ThirdOffLayoff"
+  | "Regressing"
+  | "Freshened";
+
+export interface FormLabel {
+  pattern: FormPattern;
+  evidence: string;
+}
+
+export interface FormLine {
+  race_date: string;
+  days_since_last_race: number | null;
+  speed_rating: number | null;
+  finish_position: string;
+}
+
+export interface HorseForm {
+  horse_id: number;
+  program_number: string;
+  horse_name: string;
+  days_off: number | null;
+  labels: FormLabel[];
+  lines: FormLine[];
+}
+
+export interface RaceForm {
+  race_number: number | null;
+  horses: HorseForm[];
+}
+
+export const FORM_PATTERN_LABELS: Record<FormPattern, string> = {
+  Improving: "Improving",
+  Peaked: "Peaked / bounce risk",
+  SecondOffLayoff: "2nd off layoff",
+  ThirdOffLayoff: "3rd off layoff",
+  Regressing: "Regressing",
+  Freshened: "Freshened",
+};
@@ -2,22 +2,30 @@ export class GlobalState {
   currentDirectory: string;
   downloadsDirectory: string;
   racecardsDirectory: string;
+  schemaVersion: number;
+  databaseError: string | null;
 
   constructor(
     currentDirectory: string = "",
     downloadsDirectory: string = "",
-    racecardsDirectory: string = ""
+    racecardsDirectory: string = "",
+    schemaVersion: number = 0,
+    databaseError: string | null = null
   ) {
     this.currentDirectory = currentDirectory;
     this.downloadsDirectory = downloadsDirectory;
     this.racecardsDirectory = racecardsDirectory;
+    this.schemaVersion = schemaVersion;
+    this.databaseError = databaseError;
   }
 
   static fromObject(obj: any): GlobalState {
     return new GlobalState(
       obj?.currentDirectory || "",
       obj?.downloadsDirectory || "",
-      obj?.racecardsDirectory || ""
+      obj?.racecardsDirectory || "",
+      obj?.schemaVersion ?? 0,
+      obj?.databaseError ?? null
     );
   }
 
@@ -26,6 +34,8 @@ export class GlobalState {
       currentDirectory: this.currentDirectory,
       downloadsDirectory: this.downloadsDirectory,
       racecardsDirectory: this.racecardsDirectory,
+      schemaVersion: this.schemaVersion,
+      databaseError: this.databaseError,
     };
   }
 }
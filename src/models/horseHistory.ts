@@ -0,0 +1,21 @@
+export interface HorseAppearance {
+  racecard_id: number;
+  horse_id: number;
+  date: string;
+  track_code: string;
+  race_number: number | null;
+  program_number: string;
+}
+
+// Lines and works are merged across every stored card, newest first; convert them with
+// PastPerformance.fromObject and Workout.fromObject before display.
+export interface HorseHistory {
+  horse_key: string;
+  horse_name: string;
+  year_of_birth: number | null;
+  sire: string;
+  dam: string;
+  appearances: HorseAppearance[];
+  past_performances: any[];
+  workouts: any[];
+}
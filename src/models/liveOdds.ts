@@ -0,0 +1,39 @@
+// Odds are odds-to-1; a field left out is not changed.
+export interface ToteEntry {
+  program_number: string;
+  odds?: number | null;
+  scratched?: boolean | null;
+}
+
+export interface ToteTick {
+  odds: number | null;
+  scratched: boolean | null;
+  source: string;
+  recorded_at: string;
+}
+
+export interface HorseTote {
+  horse_id: number;
+  program_number: string;
+  horse_name: string;
+  scratched: boolean;
+  current_odds: number | null;
+  ticks: ToteTick[];
+}
+
+export interface ScratchChange {
+  race_id: number;
+  horse_id: number;
+  scratched: boolean;
+}
+
+export interface LiveOddsEvent {
+  racecard_id: number;
+  race_ids: number[];
+  scratches: ScratchChange[];
+}
+
+export interface ToteImportSummary {
+  races_updated: number;
+  entries_recorded: number;
+}
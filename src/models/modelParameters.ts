@@ -0,0 +1,29 @@
+export interface ScoreWeights {
+  speed: number;
+  early: number;
+  late: number;
+  workout_bonus: number;
+}
+
+export interface ConfidenceThresholds {
+  strong_single_gap: number;
+  playable_gap: number;
+  wide_open_spread: number;
+}
+
+// Stored for the model to apply once it accepts parameters; groups left null keep its values.
+export interface ModelProfile {
+  name: string;
+  pace_heat_thresholds: [number, number, number] | null;
+  epi_cutoffs: [number, number] | null;
+  weights: ScoreWeights | null;
+  confidence: ConfidenceThresholds | null;
+}
+
+export interface ModelParameters {
+  profiles: ModelProfile[];
+  dirt_sprint: string | null;
+  dirt_route: string | null;
+  turf_sprint: string | null;
+  turf_route: string | null;
+}
@@ -0,0 +1,5 @@
+export interface PdfReport {
+  path: string;
+  pages: number;
+  bytes: number;
+}
@@ -771,6 +771,7 @@ export class PastPerformance {
   days_since_last_race: number | null;
   track_code: string;
   bris_track_code: string;
+  track_name: string;
   race_number: number | null;
   track_condition: string;
   distance: number | null;
@@ -875,6 +876,7 @@ export class PastPerformance {
     this.days_since_last_race = data.days_since_last_race ?? null;
     this.track_code = data.track_code ?? '';
     this.bris_track_code = data.bris_track_code ?? '';
+    this.track_name = data.track_name ?? '';
     this.race_number = data.race_number ?? null;
     this.track_condition = data.track_condition ?? '';
     this.distance = data.distance ?? null;
@@ -985,6 +987,7 @@ export class PastPerformance {
       days_since_last_race: this.days_since_last_race,
       track_code: this.track_code,
       bris_track_code: this.bris_track_code,
+      track_name: this.track_name,
       race_number: this.race_number,
       track_condition: this.track_condition,
       distance: this.distance,
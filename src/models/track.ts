@@ -0,0 +1,11 @@
+export interface Track {
+  code: string;
+  name: string;
+  country: string;
+  timezone: string;
+  surfaces: string[];
+  dirt_circumference: number | null;
+  turf_circumference: number | null;
+  chutes: string[];
+  aliases: string[];
+}
@@ -0,0 +1,45 @@
+export type TripOutlook = "good" | "bad" | "excusable" | "neutral";
+
+export interface TripWeights {
+  race_weights: number[];
+  max_days_back: number;
+  good: number;
+  bad: number;
+  excusable: number;
+  minor: number;
+  moderate: number;
+  major: number;
+}
+
+export interface TripEvent {
+  trouble: string;
+  severity: "Minor" | "Moderate" | "Major";
+  call_point: "Start" | "Early" | "Turn" | "Stretch" | "Unknown";
+  outlook: TripOutlook;
+  text: string;
+}
+
+export interface TripLine {
+  race_date: string;
+  track_code: string;
+  days_back: number | null;
+  comment: string;
+  events: TripEvent[];
+  outlook: TripOutlook;
+  score: number;
+}
+
+export interface HorseTrip {
+  horse_id: number;
+  program_number: string;
+  horse_name: string;
+  scratched: boolean;
+  score: number;
+  trips: TripLine[];
+}
+
+export interface TripRanking {
+  race_number: number | null;
+  weights: TripWeights;
+  horses: HorseTrip[];
+}
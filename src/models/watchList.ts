@@ -0,0 +1,28 @@
+export interface WatchListEntry {
+  horse_key: string;
+  horse_name: string;
+  year_of_birth: number | null;
+  dam: string;
+  note: string;
+  added_at: string;
+  updated_at: string;
+  appearances: number;
+  last_seen_date: string | null;
+  last_seen_track_code: string | null;
+}
+
+export interface WatchAlert {
+  horse_key: string;
+  horse_id: number;
+  horse_name: string;
+  race_number: number | null;
+  program_number: string;
+  note: string;
+}
+
+export interface WatchedHorsesEvent {
+  racecard_id: number;
+  track: string;
+  date: string;
+  horses: WatchAlert[];
+}
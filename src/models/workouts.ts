@@ -0,0 +1,60 @@
+export type WorkoutPattern =
+  | "Bullet"
+  | "RegularSpacing"
+  | "GateWork"
+  | "LayoffTab"
+  | "SharpRecent";
+
+export type WorkoutSort =
+  | "ProgramNumber"
+  | "BestPercentile"
+  | "AveragePercentile"
+  | "LatestWork"
+  | "WorkCount";
+
+export interface WorkoutQuery {
+  sort?: WorkoutSort;
+  pattern?: WorkoutPattern | null;
+  min_percentile?: number | null;
+  within_days?: number | null;
+}
+
+export interface WorkoutLine {
+  date: string;
+  days_before_race: number | null;
+  track: string;
+  distance_furlongs: number | null;
+  time: number | null;
+  seconds_per_furlong: number | null;
+  condition: string;
+  description: string;
+  training_track: string;
+  rank: number | null;
+  workers: number | null;
+  // 100 is the fastest of the day at the distance.
+  percentile: number | null;
+  bullet: boolean;
+  gate: boolean;
+  since_last_race: boolean;
+}
+
+export interface HorseWorkouts {
+  horse_id: number;
+  program_number: string;
+  horse_name: string;
+  work_count: number;
+  bullets: number;
+  gate_works: number;
+  works_since_last_race: number;
+  best_percentile: number | null;
+  average_percentile: number | null;
+  spacing_days: number | null;
+  latest_work_days: number | null;
+  patterns: WorkoutPattern[];
+  works: WorkoutLine[];
+}
+
+export interface RaceWorkouts {
+  race_number: number | null;
+  horses: HorseWorkouts[];
+}
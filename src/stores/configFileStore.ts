@@ -1,6 +1,7 @@
 import { defineStore } from 'pinia';
 import { invoke } from '@tauri-apps/api/core';
 import { ConfigState } from '../models/configState';
+import type { ModelParameters } from '../models/modelParameters';
 
 export const useConfigFileStore = defineStore('configFile', {
   state: () => ({
@@ -32,6 +33,12 @@ export const useConfigFileStore = defineStore('configFile', {
         this.loading = false;
       }
     },
+    // Saved on its own so validation errors reach the caller; the stored copy is kept in step so a
+    // later saveConfigFile doesn't write the old profiles back.
+    async saveModelParameters(modelParameters: ModelParameters): Promise<void> {
+      const saved = await invoke<ModelParameters>('save_model_parameters', { modelParameters });
+      this.configState.modelParameters = saved;
+    },
     getLastDirectory(): string {
       return this.configState.lastDirectory;
     },
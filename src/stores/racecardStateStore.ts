@@ -4,6 +4,15 @@ import { RacecardState } from "../models/racecardState";
 import { Racecard, Race } from "../models/racecard";
 import { RacecardEntry, Racecards } from "../models/racecards";
 import { RaceMeta } from "../models/analysis";
+import type { RaceMetaUpdate } from "../models/analysis";
+import type { RaceBias } from "../models/bias";
+import type { FairOddsLine } from "../models/fairOdds";
+import type { RaceFigures } from "../models/figures";
+import type { RaceClass } from "../models/class";
+import type { RaceForm } from "../models/formCycle";
+import type { RaceWorkouts, WorkoutQuery } from "../models/workouts";
+import type { LiveOddsEvent } from "../models/liveOdds";
+import type { TripLine, TripRanking, TripWeights } from "../models/trip";
 
 function isRacecardIdxValid(idx: number, racecardState: RacecardState): boolean {
     return (
@@ -13,6 +22,13 @@ function isRacecardIdxValid(idx: number, racecardState: RacecardState): boolean
 
 let saveNoteTimeouts = new Map<number, ReturnType<typeof setTimeout>>();
 let raceMetaRequestId = 0;
+let raceBiasRequestId = 0;
+let tripRequestId = 0;
+let fairOddsRequestId = 0;
+let figuresRequestId = 0;
+let classRequestId = 0;
+let formRequestId = 0;
+let workoutsRequestId = 0;
 
 export type TripInfo = {
     scratched: boolean;
@@ -55,6 +71,14 @@ export const useRacecardStateStore = defineStore("RacecardState", {
         currentRaceNumber: 1,
         raceMeta: null as RaceMeta | null,
         tripData: [] as TripInfo[],
+        tripWeights: null as TripWeights | null,
+        raceBiases: [] as RaceBias[],
+        fairOdds: null as FairOddsLine | null,
+        raceFigures: null as RaceFigures | null,
+        raceClass: null as RaceClass | null,
+        raceForm: null as RaceForm | null,
+        raceWorkouts: null as RaceWorkouts | null,
+        workoutQuery: {} as WorkoutQuery,
     }),
     getters: {
         getCurrentRacecardIdx(): number {
@@ -78,9 +102,16 @@ export const useRacecardStateStore = defineStore("RacecardState", {
         getTripData(): TripInfo[] {
             return this.tripData;
         },
+        getCurrentRaceBias(): RaceBias | null {
+            const race = this.getCurrentRacecard?.races?.[this.currentRaceNumber - 1];
+            if (!race) {
+                return null;
+            }
+            return this.raceBiases.find(b => b.race_id === race.id) ?? null;
+        },
     },
     actions: {
-        updateTripData(): void {
+        async updateTripData(): Promise<void> {
             const currentRacecard = this.getCurrentRacecard;
             if (!currentRacecard) {
                 this.tripData = [];
@@ -94,33 +125,40 @@ export const useRacecardStateStore = defineStore("RacecardState", {
                 return;
             }
 
-            const trips: Array<TripInfo> = [];
-            for (const horse of race.horses || []) {
-                if (!horse.trip_handicapping_info) continue;
-                const cols = horse.trip_handicapping_info.split(",");
-                const tripInfo: TripInfo = {
-                    scratched: horse.scratched,
-                    program_number: horse.program_number,
-                    horse_name: horse.horse_name,
-                    score: Number(cols[0]),
-                    days_back_1: Number(cols[1]),
-                    comment_1: cols[2],
-                    days_back_2: Number(cols[3]),
-                    comment_2: cols[4],
-                    days_back_3: Number(cols[5]),
-                    comment_3: cols[6],
-                };
-
-                trips.push(tripInfo);
-            }
-
-            this.tripData = trips
-                .sort((a, b) => {
-                    const scoreA = Number.isFinite(a.score) ? (a.score as number) : Number.NEGATIVE_INFINITY;
-                    const scoreB = Number.isFinite(b.score) ? (b.score as number) : Number.NEGATIVE_INFINITY;
-                    return scoreB - scoreA;
-                })
-                .map((item) => item);
+            const requestId = ++tripRequestId;
+            try {
+                const result = await invoke<TripRanking>("rank_trips", {
+                    race: Race.fromObject(race).toObject(),
+                    racecardDate: currentRacecard.date ?? null,
+                });
+                if (requestId !== tripRequestId) {
+                    return;
+                }
+
+                // The ranking comes back sorted by score.
+                this.tripWeights = result.weights;
+                this.tripData = result.horses.map((horse) => {
+                    const [trip1, trip2, trip3] = horse.trips;
+                    const comment = (trip?: TripLine) => (trip ? `${trip.outlook}: ${trip.comment}` : undefined);
+                    return {
+                        scratched: horse.scratched,
+                        program_number: horse.program_number,
+                        horse_name: horse.horse_name,
+                        score: horse.score,
+                        days_back_1: trip1?.days_back ?? undefined,
+                        comment_1: comment(trip1),
+                        days_back_2: trip2?.days_back ?? undefined,
+                        comment_2: comment(trip2),
+                        days_back_3: trip3?.days_back ?? undefined,
+                        comment_3: comment(trip3),
+                    };
+                });
+            } catch (err) {
+                console.error("Failed to rank trips", err);
+                if (requestId === tripRequestId) {
+                    this.tripData = [];
+                }
+            }
         },
         async updateRaceMeta(): Promise<void> {
             const currentRacecard = this.getCurrentRacecard;
@@ -152,6 +190,183 @@ export const useRacecardStateStore = defineStore("RacecardState", {
                 }
             }
         },
+        // Live odds are program number -> odds-to-1 as entered; without them only the morning line is compared.
+        async updateFairOdds(liveOdds: Record<string, number> | null = null): Promise<void> {
+            const currentRacecard = this.getCurrentRacecard;
+            const race = currentRacecard?.races?.[this.currentRaceNumber - 1];
+            if (!currentRacecard || !race) {
+                this.fairOdds = null;
+                return;
+            }
+
+            const requestId = ++fairOddsRequestId;
+            try {
+                const result = await invoke<FairOddsLine>("get_fair_odds", {
+                    race: Race.fromObject(race).toObject(),
+                    racecardDate: currentRacecard.date ?? null,
+                    liveOdds,
+                });
+                if (requestId === fairOddsRequestId) {
+                    this.fairOdds = result;
+                }
+            } catch (err) {
+                console.error("Failed to compute fair odds", err);
+                if (requestId === fairOddsRequestId) {
+                    this.fairOdds = null;
+                }
+            }
+        },
+        async updateFigures(): Promise<void> {
+            const race = this.getCurrentRacecard?.races?.[this.currentRaceNumber - 1];
+            if (!race) {
+                this.raceFigures = null;
+                return;
+            }
+
+            const requestId = ++figuresRequestId;
+            try {
+                const result = await invoke<RaceFigures>("get_figures", {
+                    race: Race.fromObject(race).toObject(),
+                });
+                if (requestId === figuresRequestId) {
+                    this.raceFigures = result;
+                }
+            } catch (err) {
+                console.error("Failed to compute figures", err);
+                if (requestId === figuresRequestId) {
+                    this.raceFigures = null;
+                }
+            }
+        },
+        async updateRaceClass(): Promise<void> {
+            const race = this.getCurrentRacecard?.races?.[this.currentRaceNumber - 1];
+            if (!race) {
+                this.raceClass = null;
+                return;
+            }
+
+            const requestId = ++classRequestId;
+            try {
+                const result = await invoke<RaceClass>("get_race_class", {
+                    race: Race.fromObject(race).toObject(),
+                });
+                if (requestId === classRequestId) {
+                    this.raceClass = result;
+                }
+            } catch (err) {
+                console.error("Failed to compute class analysis", err);
+                if (requestId === classRequestId) {
+                    this.raceClass = null;
+                }
+            }
+        },
+        async updateRaceForm(): Promise<void> {
+            const race = this.getCurrentRacecard?.races?.[this.currentRaceNumber - 1];
+            if (!race) {
+                this.raceForm = null;
+                return;
+            }
+
+            const requestId = ++formRequestId;
+            try {
+                const result = await invoke<RaceForm>("get_form_cycles", {
+                    race: Race.fromObject(race).toObject(),
+                });
+                if (requestId === formRequestId) {
+                    this.raceForm = result;
+                }
+            } catch (err) {
+                console.error("Failed to compute form cycles", err);
+                if (requestId === formRequestId) {
+                    this.raceForm = null;
+                }
+            }
+        },
+        // The query is kept, so a sort or filter chosen in one race carries over to the next.
+        async updateWorkoutReport(query: WorkoutQuery | null = null): Promise<void> {
+            if (query) {
+                this.workoutQuery = query;
+            }
+            const currentRacecard = this.getCurrentRacecard;
+            const race = currentRacecard?.races?.[this.currentRaceNumber - 1];
+            if (!currentRacecard || !race) {
+                this.raceWorkouts = null;
+                return;
+            }
+
+            const requestId = ++workoutsRequestId;
+            try {
+                const result = await invoke<RaceWorkouts>("get_workout_report", {
+                    race: Race.fromObject(race).toObject(),
+                    racecardDate: currentRacecard.date ?? null,
+                    query: this.workoutQuery,
+                });
+                if (requestId === workoutsRequestId) {
+                    this.raceWorkouts = result;
+                }
+            } catch (err) {
+                console.error("Failed to build workout report", err);
+                if (requestId === workoutsRequestId) {
+                    this.raceWorkouts = null;
+                }
+            }
+        },
+        async updateRaceBiases(): Promise<void> {
+            const currentRacecard = this.getCurrentRacecard;
+            if (!currentRacecard?.id) {
+                this.raceBiases = [];
+                return;
+            }
+
+            const requestId = ++raceBiasRequestId;
+            try {
+                const result = await invoke<RaceBias[]>("get_racecard_bias", { racecardId: currentRacecard.id });
+                if (requestId === raceBiasRequestId) {
+                    this.raceBiases = result;
+                }
+            } catch (err) {
+                console.error("Failed to load track bias", err);
+                if (requestId === raceBiasRequestId) {
+                    this.raceBiases = [];
+                }
+            }
+        },
+        // The backend reranked a race after a scratch change; this supersedes any rank_race call
+        // still in flight.
+        applyRaceMetaUpdate(update: RaceMetaUpdate): void {
+            const race = this.getCurrentRacecard?.races?.[this.currentRaceNumber - 1];
+            if (this.getCurrentRacecard?.id !== update.racecard_id || race?.id !== update.race_id) {
+                return;
+            }
+
+            ++raceMetaRequestId;
+            this.raceMeta = RaceMeta.fromObject(update.meta);
+        },
+        // Odds or scratches were entered for this card elsewhere; pick up the scratches and refresh
+        // the race on screen when it is one of those updated. The backend sends the new analysis.
+        applyLiveOddsUpdate(update: LiveOddsEvent): void {
+            const entry = this.racecardState.racecards.racecardEntries.find(e => e.racecard.id === update.racecard_id);
+            if (!entry) {
+                return;
+            }
+
+            for (const change of update.scratches) {
+                updateHorseScratch(entry.racecard, change.horse_id, change.scratched);
+                const currentRacecard = this.getCurrentRacecard;
+                if (currentRacecard && currentRacecard !== entry.racecard) {
+                    updateHorseScratch(currentRacecard, change.horse_id, change.scratched);
+                }
+            }
+
+            const race = this.getCurrentRacecard?.races?.[this.currentRaceNumber - 1];
+            if (this.getCurrentRacecard?.id !== update.racecard_id || !race || !update.race_ids.includes(race.id)) {
+                return;
+            }
+            if (update.scratches.length > 0) {
+                void this.updateTripData();
+            }
+            void this.updateFairOdds();
+        },
         setNextRaceNumber(): void {
             const currentRacecard = this.getCurrentRacecard;
             if (!currentRacecard) {
@@ -188,7 +403,12 @@ export const useRacecardStateStore = defineStore("RacecardState", {
             
             this.racecardState.racecards.racecardEntries[this.racecardState.currentRacecardIdx].last_opened_race = raceNumber;
             void this.updateRaceMeta();
-            this.updateTripData();
+            void this.updateFairOdds();
+            void this.updateFigures();
+            void this.updateRaceClass();
+            void this.updateRaceForm();
+            void this.updateWorkoutReport();
+            void this.updateTripData();
         },
         setCurrentRacecardIdx(idx: number): void {
             const entries = this.racecardState.racecards.racecardEntries;
@@ -196,7 +416,13 @@ export const useRacecardStateStore = defineStore("RacecardState", {
                 this.racecardState.currentRacecardIdx = 0;
                 this.currentRaceNumber = 1;
                 this.raceMeta = null;
+                this.fairOdds = null;
+                this.raceFigures = null;
+                this.raceClass = null;
+                this.raceForm = null;
+                this.raceWorkouts = null;
                 this.tripData = [];
+                this.raceBiases = [];
                 return;
             }
 
@@ -212,7 +438,13 @@ export const useRacecardStateStore = defineStore("RacecardState", {
             const entry = entries[idx];
             this.currentRaceNumber = entry.last_opened_race > 0 ? entry.last_opened_race : 1;
             void this.updateRaceMeta();
-            this.updateTripData();
+            void this.updateFairOdds();
+            void this.updateFigures();
+            void this.updateRaceClass();
+            void this.updateRaceForm();
+            void this.updateWorkoutReport();
+            void this.updateRaceBiases();
+            void this.updateTripData();
         },
         addRacecard(racecard: Racecard): void {
             this.racecardState.racecards.racecardEntries.push(
@@ -266,7 +498,8 @@ export const useRacecardStateStore = defineStore("RacecardState", {
             });
 
             void this.updateRaceMeta();
-            this.updateTripData();
+            void this.updateFairOdds();
+            void this.updateTripData();
         },
 
         deleteRacecardAt(index: number): void {